@@ -0,0 +1,339 @@
+//! interactive dashboard for users and sessions, `keyauth-cli dashboard`
+
+use std::time::{Duration, Instant, SystemTime};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table, TableState, Tabs};
+use ratatui::{DefaultTerminal, Frame};
+use keyauth::seller::{empty_if_none_found, SellerClient};
+use keyauth::seller::sessions::Session;
+use keyauth::seller::user::{User, UserData, UserVar};
+
+const HELP_USERS: &str = "↑↓ select  b ban  u unban  h reset hwid  e extend  r refresh  tab sessions  q quit";
+const HELP_SESSIONS: &str = "↑↓ select  x kill  K kill all  r refresh  tab users  q quit";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    Users,
+    Sessions,
+}
+
+enum Prompt {
+    BanReason(String),
+    /// "subscription days"
+    Extend(String),
+    KillAll,
+}
+
+struct Dashboard {
+    client: SellerClient,
+    refresh: Duration,
+    last_refresh: Instant,
+    tab: Tab,
+    users: Vec<User>,
+    users_state: TableState,
+    /// user_data and vars of the selected user
+    detail: Option<(String, Result<UserData, String>, Vec<UserVar>)>,
+    vars: Vec<UserVar>,
+    sessions: Vec<Session>,
+    sessions_state: TableState,
+    prompt: Option<(Prompt, String)>,
+    status: String,
+}
+
+pub fn run(client: SellerClient, refresh: Duration) -> Result<(), String> {
+    let mut terminal = ratatui::init();
+    let mut dashboard = Dashboard::new(client, refresh);
+    dashboard.refresh();
+    let result = dashboard.event_loop(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl Dashboard {
+    fn new(client: SellerClient, refresh: Duration) -> Self {
+        Self {
+            client,
+            refresh,
+            last_refresh: Instant::now(),
+            tab: Tab::Users,
+            users: Vec::new(),
+            users_state: TableState::default().with_selected(0),
+            detail: None,
+            vars: Vec::new(),
+            sessions: Vec::new(),
+            sessions_state: TableState::default().with_selected(0),
+            prompt: None,
+            status: String::new(),
+        }
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<(), String> {
+        loop {
+            terminal.draw(|frame| self.draw(frame)).map_err(|e| e.to_string())?;
+            let timeout = self.refresh.saturating_sub(self.last_refresh.elapsed());
+            if event::poll(timeout).map_err(|e| e.to_string())? {
+                if let Event::Key(key) = event::read().map_err(|e| e.to_string())? {
+                    if key.kind == KeyEventKind::Press && !self.key(key.code) {
+                        return Ok(());
+                    }
+                }
+            }
+            if self.last_refresh.elapsed() >= self.refresh {
+                self.refresh();
+            }
+        }
+    }
+
+    /// reloads users, vars and sessions, keeps the selection.
+    /// a list that fails to load keeps what was shown before, the errors are returned
+    fn reload(&mut self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        match empty_if_none_found(self.client.users().fetch_all_users()) {
+            Ok(users) => self.users = users,
+            Err(err) => errors.push(format!("users: {}", err)),
+        }
+        match empty_if_none_found(self.client.users().fetch_all_vars()) {
+            Ok(vars) => self.vars = vars,
+            Err(err) => errors.push(format!("user variables: {}", err)),
+        }
+        match empty_if_none_found(self.client.sessions().list_all()) {
+            Ok(sessions) => self.sessions = sessions,
+            Err(err) => errors.push(format!("sessions: {}", err)),
+        }
+        clamp(&mut self.users_state, self.users.len());
+        clamp(&mut self.sessions_state, self.sessions.len());
+        self.detail = None;
+        self.load_detail();
+        self.last_refresh = Instant::now();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("error loading {}", errors.join(", ")))
+        }
+    }
+
+    /// reload that shows its errors in the status line
+    fn refresh(&mut self) {
+        if let Err(err) = self.reload() {
+            self.status = err;
+        }
+    }
+
+    fn selected_user(&self) -> Option<&User> {
+        self.users.get(self.users_state.selected()?)
+    }
+
+    fn load_detail(&mut self) {
+        let Some(name) = self.selected_user().map(|u| u.username.clone()) else {
+            self.detail = None;
+            return;
+        };
+        if self.detail.as_ref().is_some_and(|(n, _, _)| *n == name) {
+            return;
+        }
+        let data = self.client.users().user_data(&name);
+        let vars = self.vars.iter().filter(|v| v.user == name).cloned().collect();
+        self.detail = Some((name, data, vars));
+    }
+
+    /// handles a key, false quits
+    fn key(&mut self, code: KeyCode) -> bool {
+        if let Some((prompt, mut input)) = self.prompt.take() {
+            match code {
+                KeyCode::Enter => self.submit(prompt, input),
+                KeyCode::Esc => self.status = "cancelled".to_string(),
+                KeyCode::Backspace => {
+                    input.pop();
+                    self.prompt = Some((prompt, input));
+                }
+                KeyCode::Char(c) => {
+                    input.push(c);
+                    self.prompt = Some((prompt, input));
+                }
+                _ => self.prompt = Some((prompt, input)),
+            }
+            return true;
+        }
+        let user = self.selected_user().map(|u| u.username.clone());
+        match (self.tab, code) {
+            (_, KeyCode::Char('q')) | (_, KeyCode::Esc) => return false,
+            (_, KeyCode::Tab) => self.tab = if self.tab == Tab::Users { Tab::Sessions } else { Tab::Users },
+            (_, KeyCode::Char('r')) => {
+                self.status = "refreshed".to_string();
+                self.refresh();
+            }
+            (Tab::Users, KeyCode::Up | KeyCode::Char('k')) => {
+                self.users_state.select_previous();
+                self.load_detail();
+            }
+            (Tab::Users, KeyCode::Down | KeyCode::Char('j')) => {
+                self.users_state.select_next();
+                clamp(&mut self.users_state, self.users.len());
+                self.load_detail();
+            }
+            (Tab::Sessions, KeyCode::Up | KeyCode::Char('k')) => self.sessions_state.select_previous(),
+            (Tab::Sessions, KeyCode::Down | KeyCode::Char('j')) => {
+                self.sessions_state.select_next();
+                clamp(&mut self.sessions_state, self.sessions.len());
+            }
+            (Tab::Users, KeyCode::Char('b')) if user.is_some() => self.prompt = Some((Prompt::BanReason(user.unwrap()), String::new())),
+            (Tab::Users, KeyCode::Char('e')) if user.is_some() => self.prompt = Some((Prompt::Extend(user.unwrap()), String::new())),
+            (Tab::Users, KeyCode::Char('u')) => {
+                if let Some(user) = user {
+                    self.act(|c| c.users().unban(&user));
+                }
+            }
+            (Tab::Users, KeyCode::Char('h')) => {
+                if let Some(user) = user {
+                    self.act(|c| c.users().reset_hwid(&user));
+                }
+            }
+            (Tab::Sessions, KeyCode::Char('x')) => {
+                let id = self.sessions_state.selected().and_then(|i| self.sessions.get(i)).map(|s| s.id.clone());
+                if let Some(id) = id {
+                    self.act(|c| c.sessions().kill(&id));
+                }
+            }
+            (Tab::Sessions, KeyCode::Char('K')) => self.prompt = Some((Prompt::KillAll, String::new())),
+            _ => {}
+        }
+        true
+    }
+
+    fn submit(&mut self, prompt: Prompt, input: String) {
+        match prompt {
+            Prompt::BanReason(user) => {
+                let reason = Some(input.trim().to_string()).filter(|r| !r.is_empty());
+                self.act(|c| c.users().ban(&user, reason));
+            }
+            Prompt::Extend(user) => {
+                let mut parts = input.split_whitespace();
+                match (parts.next(), parts.next().and_then(|d| d.parse::<u64>().ok())) {
+                    (Some(sub), Some(days)) => self.act(|c| c.users().extend_user_subscription(&user, sub, &days.to_string(), None)),
+                    _ => self.status = "enter the subscription and the days, e.g. \"default 30\"".to_string(),
+                }
+            }
+            Prompt::KillAll if input.trim() == "yes" => self.act(|c| c.sessions().kill_all()),
+            Prompt::KillAll => self.status = "not killing sessions".to_string(),
+        }
+    }
+
+    /// runs an action, shows its result and reloads
+    fn act(&mut self, f: impl FnOnce(&SellerClient) -> Result<String, String>) {
+        let result = f(&self.client);
+        let reloaded = self.reload();
+        self.status = match result {
+            Ok(message) => message,
+            Err(err) => format!("error: {}", err),
+        };
+        if let Err(err) = reloaded {
+            self.status = format!("{}, {}", self.status, err);
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [tabs, body, status] = Layout::vertical([Constraint::Length(1), Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+        let selected = if self.tab == Tab::Users { 0 } else { 1 };
+        frame.render_widget(Tabs::new([format!("users ({})", self.users.len()), format!("sessions ({})", self.sessions.len())]).select(selected)
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED)), tabs);
+
+        let highlight = Style::new().bg(Color::DarkGray).add_modifier(Modifier::BOLD);
+        match self.tab {
+            Tab::Users => {
+                let [list, detail] = Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)]).areas(body);
+                let rows = self.users.iter().map(|u| {
+                    let style = if u.banned.as_deref().is_some_and(|b| !b.is_empty()) { Style::new().fg(Color::Red) } else { Style::new() };
+                    Row::new([u.username.clone(), u.hwid.clone().unwrap_or_default(), time(u.lastlogin)]).style(style)
+                });
+                let table = Table::new(rows, [Constraint::Percentage(35), Constraint::Percentage(35), Constraint::Percentage(30)])
+                    .header(Row::new(["username", "hwid", "last login"]).style(Style::new().add_modifier(Modifier::BOLD)))
+                    .block(Block::new().borders(Borders::ALL).title("users"))
+                    .row_highlight_style(highlight);
+                frame.render_stateful_widget(table, list, &mut self.users_state);
+                frame.render_widget(Paragraph::new(self.detail_lines()).block(Block::new().borders(Borders::ALL).title("user")), detail);
+            }
+            Tab::Sessions => {
+                let rows = self.sessions.iter().map(|s| Row::new([
+                    s.id.clone(),
+                    s.credential.clone().unwrap_or_default(),
+                    s.ip.clone().unwrap_or_default(),
+                    if s.validated { "yes".to_string() } else { "no".to_string() },
+                    time(Some(s.expiry)),
+                ]));
+                let table = Table::new(rows, [Constraint::Percentage(25), Constraint::Percentage(20), Constraint::Percentage(20), Constraint::Percentage(10), Constraint::Percentage(25)])
+                    .header(Row::new(["id", "user", "ip", "validated", "expires"]).style(Style::new().add_modifier(Modifier::BOLD)))
+                    .block(Block::new().borders(Borders::ALL).title("sessions"))
+                    .row_highlight_style(highlight);
+                frame.render_stateful_widget(table, body, &mut self.sessions_state);
+            }
+        }
+
+        let line = match &self.prompt {
+            Some((Prompt::BanReason(user), input)) => format!("ban reason for {}: {}_", user, input),
+            Some((Prompt::Extend(user), input)) => format!("extend {} (subscription days): {}_", user, input),
+            Some((Prompt::KillAll, input)) => format!("type yes to kill every session: {}_", input),
+            None if !self.status.is_empty() => self.status.clone(),
+            None => if self.tab == Tab::Users { HELP_USERS } else { HELP_SESSIONS }.to_string(),
+        };
+        frame.render_widget(Paragraph::new(line), status);
+    }
+
+    fn detail_lines(&self) -> Vec<Line<'static>> {
+        let Some((name, data, vars)) = &self.detail else {
+            return vec![Line::from("no user selected")];
+        };
+        let mut lines = vec![Line::from(format!("username  {}", name))];
+        if let Some(reason) = self.selected_user().and_then(|u| u.banned.clone()).filter(|b| !b.is_empty()) {
+            lines.push(Line::styled(format!("banned    {}", reason), Style::new().fg(Color::Red)));
+        }
+        match data {
+            Ok(data) => {
+                lines.push(Line::from(format!("hwid      {}", data.hwid.clone().unwrap_or_default())));
+                lines.push(Line::from(format!("ip        {}", data.ip.clone().unwrap_or_default())));
+                lines.push(Line::from(format!("created   {}", time(Some(data.createdate)))));
+                lines.push(Line::from(""));
+                lines.push(Line::styled("subscriptions", Style::new().add_modifier(Modifier::BOLD)));
+                for sub in &data.subscriptions {
+                    lines.push(Line::from(format!("  {}  until {}", sub.subscription, time(Some(sub.expiry)))));
+                }
+            }
+            Err(err) => lines.push(Line::styled(format!("user data: {}", err), Style::new().fg(Color::Red))),
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::styled("variables", Style::new().add_modifier(Modifier::BOLD)));
+        for var in vars {
+            lines.push(Line::from(format!("  {} = {}", var.name, var.data)));
+        }
+        lines
+    }
+}
+
+fn clamp(state: &mut TableState, len: usize) {
+    match state.selected() {
+        _ if len == 0 => state.select(None),
+        Some(i) if i >= len => state.select(Some(len - 1)),
+        None => state.select(Some(0)),
+        _ => {}
+    }
+}
+
+/// unix seconds as a relative time, e.g. "3d ago" or "in 5h"
+fn time(time: Option<SystemTime>) -> String {
+    let Some(time) = time else {
+        return String::new();
+    };
+    let (diff, past) = match SystemTime::now().duration_since(time) {
+        Ok(ago) => (ago.as_secs(), true),
+        Err(ahead) => (ahead.duration().as_secs(), false),
+    };
+    let amount = match diff {
+        d if d < 60 => format!("{}s", d),
+        d if d < 3600 => format!("{}m", d / 60),
+        d if d < 86400 => format!("{}h", d / 3600),
+        d => format!("{}d", d / 86400),
+    };
+    if past { format!("{} ago", amount) } else { format!("in {}", amount) }
+}
@@ -0,0 +1,395 @@
+//! command line access to the seller api, build with `--features cli`
+//!
+//! the seller key is taken from `--seller-key`, `KEYAUTH_SELLER_KEY` or the `seller_key` of the config file
+//! (`--config`, `KEYAUTH_CONFIG` or `~/.config/keyauth/cli.toml`), the url the same way with `url`
+//!
+//! with the `tui` feature `dashboard` opens an interactive view of users and sessions, and with the `mock` feature
+//! `mock-server` runs an in memory seller api to try things without a real app:
+//! `keyauth-cli mock-server` and in another terminal
+//! `keyauth-cli --url http://127.0.0.1:8765/ --seller-key mock-seller-key dashboard`
+
+#[cfg(feature = "tui")]
+mod dashboard;
+#[cfg(test)]
+mod test;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use serde_json::Value;
+use keyauth::seller::{empty_if_none_found, SellerClient};
+use keyauth::seller::licenses::{Charset, License, LicenseSpec, LicenseStatus};
+use keyauth::seller::settings::SettingsPatch;
+
+const DEFAULT_URL: &str = "https://keyauth.win/api/seller/";
+
+#[derive(Parser)]
+#[command(name = "keyauth-cli", version, about = "keyauth seller api from the command line")]
+struct Cli {
+    #[arg(long, env = "KEYAUTH_SELLER_KEY", hide_env_values = true, global = true)]
+    seller_key: Option<String>,
+    #[arg(long, env = "KEYAUTH_SELLER_URL", global = true)]
+    url: Option<String>,
+    #[arg(long, env = "KEYAUTH_CONFIG", global = true)]
+    config: Option<PathBuf>,
+    /// print json instead of a table
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(subcommand)]
+    Licenses(Licenses),
+    #[command(subcommand)]
+    Users(Users),
+    #[command(subcommand)]
+    Vars(Vars),
+    #[command(subcommand)]
+    Sessions(Sessions),
+    #[command(subcommand)]
+    Settings(Settings),
+    #[command(subcommand)]
+    Blacklist(Blacklist),
+    /// interactive view of users and sessions
+    #[cfg(feature = "tui")]
+    Dashboard {
+        /// seconds between automatic refreshes
+        #[arg(long, default_value_t = 10)]
+        refresh: u64,
+    },
+    /// runs an in memory seller api with demo data until killed
+    #[cfg(feature = "mock")]
+    MockServer {
+        #[arg(long, default_value = "127.0.0.1:8765")]
+        addr: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum Licenses {
+    /// creates licenses, any amount is split into batches
+    Create {
+        #[arg(long)]
+        days: f64,
+        #[arg(long)]
+        mask: Option<String>,
+        #[arg(long, default_value_t = 1)]
+        level: u32,
+        #[arg(long, default_value_t = 1)]
+        amount: u32,
+        #[arg(long)]
+        owner: Option<String>,
+        #[arg(long)]
+        note: Option<String>,
+        #[arg(long, value_enum, default_value_t = CharsetArg::Random)]
+        charset: CharsetArg,
+    },
+    Verify { key: String },
+    Delete {
+        key: String,
+        /// delete the user that used the key too
+        #[arg(long)]
+        user_too: bool,
+    },
+    List {
+        #[arg(long)]
+        unused: bool,
+        #[arg(long)]
+        expired: bool,
+        #[arg(long)]
+        used_by: Option<String>,
+    },
+    Ban {
+        key: String,
+        #[arg(long)]
+        reason: String,
+        #[arg(long)]
+        user_too: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum CharsetArg {
+    Random,
+    Uppercase,
+    Lowercase,
+}
+
+#[derive(Subcommand)]
+enum Users {
+    List,
+    Ban {
+        name: String,
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    ResetHwid { name: String },
+    /// extends a subscription of the user
+    Extend {
+        name: String,
+        #[arg(long)]
+        sub: String,
+        #[arg(long)]
+        days: u64,
+        /// only extend if the subscription is still active
+        #[arg(long)]
+        active_only: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum Vars {
+    List,
+    Get { name: String },
+    Create {
+        name: String,
+        value: String,
+        /// only logged in users can read it
+        #[arg(long)]
+        authed: bool,
+    },
+    Set { name: String, value: String },
+    Delete { name: String },
+}
+
+#[derive(Subcommand)]
+enum Sessions {
+    List,
+    KillAll,
+}
+
+#[derive(Subcommand)]
+enum Settings {
+    Get,
+    /// sets one setting, the names are the ones settings get prints
+    Set { name: String, value: String },
+}
+
+#[derive(Subcommand)]
+enum Blacklist {
+    List,
+    Add {
+        #[arg(long)]
+        ip: Option<String>,
+        #[arg(long)]
+        hwid: Option<String>,
+    },
+    Delete {
+        data: String,
+        /// ip or hwid
+        #[arg(long = "type")]
+        blacktype: String,
+    },
+    Whitelist { ip: String },
+    Unwhitelist { ip: String },
+}
+
+#[derive(Default, serde::Deserialize)]
+struct FileConfig {
+    seller_key: Option<String>,
+    url: Option<String>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn client(cli: &Cli) -> Result<SellerClient, String> {
+    let (key, url) = seller_config(cli)?;
+    Ok(SellerClient::new(&key, url))
+}
+
+/// seller key and url, the flags and environment variables win over the config file
+fn seller_config(cli: &Cli) -> Result<(String, String), String> {
+    let path = cli.config.clone().or_else(|| {
+        let base = std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(base.join("keyauth").join("cli.toml"))
+    });
+    let file = match path {
+        Some(path) if path.exists() || cli.config.is_some() => {
+            let s = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            toml::from_str(&s).map_err(|e| format!("{}: {}", path.display(), e))?
+        }
+        _ => FileConfig::default(),
+    };
+    let key = cli.seller_key.clone().or(file.seller_key)
+        .ok_or("no seller key, use --seller-key, KEYAUTH_SELLER_KEY or seller_key in the config file")?;
+    let url = cli.url.clone().or(file.url).unwrap_or_else(|| DEFAULT_URL.to_string());
+    Ok((key, url))
+}
+
+fn run(cli: &Cli) -> Result<(), String> {
+    #[cfg(feature = "mock")]
+    if let Command::MockServer { addr } = &cli.command {
+        use keyauth::seller::mock::{MockApp, MockServer, MOCK_SELLER_KEY};
+        let server = MockServer::bind(addr, MockApp::demo())?;
+        eprintln!("mock seller api on {} with seller key {}", server.url(), MOCK_SELLER_KEY);
+        server.wait();
+        return Ok(());
+    }
+    let client = client(cli)?;
+    let out = Output { json: cli.json };
+    match &cli.command {
+        Command::Licenses(command) => match command {
+            Licenses::Create { days, mask, level, amount, owner, note, charset } => {
+                let mut spec = LicenseSpec::new(Duration::from_secs((days * 86400.0).round() as u64)).level(*level).amount(*amount);
+                if let Some(mask) = mask {
+                    spec = spec.mask(mask.as_str());
+                }
+                spec.owner = owner.clone();
+                spec.note = note.clone();
+                spec.charset = match charset {
+                    CharsetArg::Random => Charset::Random,
+                    CharsetArg::Uppercase => Charset::Uppercase,
+                    CharsetArg::Lowercase => Charset::Lowercase,
+                };
+                let created = client.licenses().create_bulk(&spec)?;
+                for failure in &created.failures {
+                    eprintln!("{} keys not created: {}", failure.amount, failure.error);
+                }
+                let rows: Vec<_> = created.keys.iter().map(|key| serde_json::json!({ "key": key })).collect();
+                out.rows(&rows);
+                if !created.is_complete() {
+                    return Err(format!("{} of {} keys not created", created.failed(), amount));
+                }
+            }
+            Licenses::Verify { key } => out.message(client.licenses().verify_license_exists(key)?),
+            Licenses::Delete { key, user_too } => out.message(client.licenses().delete(key, Some(*user_too))?),
+            Licenses::List { unused, expired, used_by } => {
+                out.rows(&select_licenses(client.licenses().stream_all(), *unused, *expired, used_by.as_deref())?);
+            }
+            Licenses::Ban { key, reason, user_too } => out.message(client.licenses().ban(key, reason, Some(*user_too))?),
+        },
+        Command::Users(command) => match command {
+            Users::List => out.rows(&empty_if_none_found(client.users().fetch_all_users())?),
+            Users::Ban { name, reason } => out.message(client.users().ban(name, reason.clone())?),
+            Users::ResetHwid { name } => out.message(client.users().reset_hwid(name)?),
+            Users::Extend { name, sub, days, active_only } => out.message(client.users().extend_user_subscription(name, sub, &days.to_string(), Some(*active_only))?),
+        },
+        Command::Vars(command) => match command {
+            Vars::List => out.rows(&empty_if_none_found(client.variables().fetch_all())?),
+            Vars::Get { name } => out.message(client.variables().retrieve(name)?),
+            Vars::Create { name, value, authed } => out.message(client.variables().create(name, value, *authed)?),
+            Vars::Set { name, value } => out.message(client.variables().edit(name, value)?),
+            Vars::Delete { name } => out.message(client.variables().delete(name)?),
+        },
+        Command::Sessions(command) => match command {
+            Sessions::List => out.rows(&empty_if_none_found(client.sessions().list_all())?),
+            Sessions::KillAll => out.message(client.sessions().kill_all()?),
+        },
+        Command::Settings(command) => match command {
+            Settings::Get => {
+                let settings = serde_json::to_value(client.settings().retrieve()?).map_err(|e| e.to_string())?;
+                let rows: Vec<_> = settings.as_object().into_iter().flatten()
+                    .map(|(name, value)| serde_json::json!({ "name": name, "value": value }))
+                    .collect();
+                out.rows(&rows);
+            }
+            Settings::Set { name, value } => {
+                let mut patch = SettingsPatch::default();
+                patch.set(name, value)?;
+                out.message(client.settings().patch(&patch)?);
+            }
+        },
+        Command::Blacklist(command) => match command {
+            Blacklist::List => out.rows(&empty_if_none_found(client.blacklists().fetch_all())?),
+            Blacklist::Add { ip, hwid } => {
+                if ip.is_none() && hwid.is_none() {
+                    return Err("use --ip or --hwid".to_string());
+                }
+                out.message(client.blacklists().add(ip.as_deref(), hwid.as_deref())?)
+            }
+            Blacklist::Delete { data, blacktype } => out.message(client.blacklists().delete(data, blacktype)?),
+            Blacklist::Whitelist { ip } => out.message(client.blacklists().add_whitelist(ip)?),
+            Blacklist::Unwhitelist { ip } => out.message(client.blacklists().del_whitelist(ip)?),
+        },
+        #[cfg(feature = "tui")]
+        Command::Dashboard { refresh } => dashboard::run(client, Duration::from_secs((*refresh).max(1)))?,
+        #[cfg(feature = "mock")]
+        Command::MockServer { .. } => unreachable!(),
+    }
+    Ok(())
+}
+
+/// the licenses matching every filter, checked one at a time while the list streams in
+fn select_licenses<I>(licenses: I, unused: bool, expired: bool, used_by: Option<&str>) -> Result<Vec<License>, String>
+where
+    I: IntoIterator<Item = Result<License, String>>,
+{
+    let now = SystemTime::now();
+    let keep = |l: &License| (!unused || l.status == LicenseStatus::NotUsed)
+        && (!expired || l.is_expired_at(now))
+        && used_by.is_none_or(|user| l.usedby.as_deref() == Some(user));
+    empty_if_none_found(licenses.into_iter().filter(|l| l.as_ref().map_or(true, keep)).collect())
+}
+
+struct Output {
+    json: bool,
+}
+
+impl Output {
+    fn message(&self, message: String) {
+        if self.json {
+            println!("{}", serde_json::json!({ "message": message }));
+        } else {
+            println!("{}", message);
+        }
+    }
+
+    fn rows<T: Serialize>(&self, rows: &[T]) {
+        let rows: Vec<Value> = rows.iter().map(|r| serde_json::to_value(r).unwrap_or(Value::Null)).collect();
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&rows).unwrap_or_default());
+        } else {
+            print!("{}", table(&rows));
+        }
+    }
+}
+
+/// rows as a table with the keys of the first row as header, rows that arent objects one per line
+fn table(rows: &[Value]) -> String {
+    let mut out = String::new();
+    let columns: Vec<String> = match rows.first() {
+        Some(Value::Object(first)) => first.keys().cloned().collect(),
+        _ => {
+            for row in rows {
+                out += &cell(row);
+                out.push('\n');
+            }
+            return out;
+        }
+    };
+    let cells: Vec<Vec<String>> = rows.iter().map(|row| columns.iter().map(|c| cell(&row[c])).collect()).collect();
+    let widths: Vec<usize> = columns.iter().enumerate()
+        .map(|(i, c)| cells.iter().map(|r| r[i].chars().count()).chain([c.len()]).max().unwrap_or(0))
+        .collect();
+    let line = |cells: &[String]| cells.iter().zip(&widths)
+        .map(|(c, w)| format!("{:<w$}", c, w = w))
+        .collect::<Vec<_>>().join("  ").trim_end().to_string() + "\n";
+    out += &line(&columns);
+    for row in &cells {
+        out += &line(row);
+    }
+    out
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,124 @@
+use clap::Parser;
+use serde_json::json;
+use super::*;
+
+fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+    Cli::try_parse_from(std::iter::once("keyauth-cli").chain(args.iter().copied()))
+}
+
+#[test]
+fn argument_parsing() {
+    let cli = parse(&["users", "ban", "alice", "--reason", "chargeback", "--json"]).unwrap();
+    assert!(cli.json);
+    assert!(matches!(&cli.command, Command::Users(Users::Ban { name, reason }) if name == "alice" && reason.as_deref() == Some("chargeback")));
+
+    // global options work before and after the subcommand
+    let cli = parse(&["--url", "http://localhost/", "licenses", "delete", "KEY-1", "--user-too", "--seller-key", "k"]).unwrap();
+    assert_eq!(cli.url.as_deref(), Some("http://localhost/"));
+    assert_eq!(cli.seller_key.as_deref(), Some("k"));
+    assert!(matches!(&cli.command, Command::Licenses(Licenses::Delete { key, user_too: true }) if key == "KEY-1"));
+
+    let cli = parse(&["licenses", "create", "--days", "1.5", "--charset", "uppercase"]).unwrap();
+    match cli.command {
+        Command::Licenses(Licenses::Create { days, amount, level, charset, mask, .. }) => {
+            assert_eq!((days, amount, level, mask), (1.5, 1, 1, None));
+            assert!(matches!(charset, CharsetArg::Uppercase));
+        }
+        _ => panic!("wrong command"),
+    }
+    assert!(matches!(parse(&["blacklist", "delete", "1.2.3.4", "--type", "ip"]).unwrap().command,
+        Command::Blacklist(Blacklist::Delete { blacktype, .. }) if blacktype == "ip"));
+
+    assert!(parse(&["licenses", "create"]).is_err());
+    assert!(parse(&["users", "extend", "alice", "--sub", "default", "--days", "many"]).is_err());
+    assert!(parse(&["users"]).is_err());
+}
+
+#[test]
+fn config_precedence() {
+    let dir = std::env::temp_dir().join(format!("keyauth-cli-config-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("cli.toml");
+    std::fs::write(&path, "seller_key = \"file-key\"\nurl = \"http://file/\"\n").unwrap();
+    let config = path.to_str().unwrap();
+
+    let resolved = |args: &[&str]| seller_config(&parse(args).unwrap());
+    let pair = |key: &str, url: &str| Ok((key.to_string(), url.to_string()));
+    assert_eq!(resolved(&["--config", config, "users", "list"]), pair("file-key", "http://file/"));
+    assert_eq!(resolved(&["--config", config, "--seller-key", "flag-key", "users", "list"]), pair("flag-key", "http://file/"));
+
+    // the only test that touches these, the variables are read by clap while parsing
+    std::env::set_var("KEYAUTH_SELLER_KEY", "env-key");
+    std::env::set_var("KEYAUTH_SELLER_URL", "http://env/");
+    let env = resolved(&["--config", config, "users", "list"]);
+    let flag = resolved(&["--config", config, "--seller-key", "flag-key", "--url", "http://flag/", "users", "list"]);
+    std::env::remove_var("KEYAUTH_SELLER_KEY");
+    std::env::remove_var("KEYAUTH_SELLER_URL");
+    assert_eq!(env, pair("env-key", "http://env/"));
+    assert_eq!(flag, pair("flag-key", "http://flag/"));
+
+    std::fs::write(&path, "").unwrap();
+    assert_eq!(resolved(&["--config", config, "--seller-key", "k", "users", "list"]), pair("k", DEFAULT_URL));
+    assert!(resolved(&["--config", config, "users", "list"]).unwrap_err().starts_with("no seller key"));
+    std::fs::write(&path, "seller_key = ").unwrap();
+    assert!(resolved(&["--config", config, "users", "list"]).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(resolved(&["--config", config, "--seller-key", "k", "users", "list"]).is_err());
+}
+
+#[test]
+fn table_output() {
+    let rows = [
+        json!({ "username": "alice", "hwid": "HWID-1", "banned": null }),
+        json!({ "username": "bob", "hwid": null, "banned": true }),
+    ];
+    assert_eq!(table(&rows), "banned  hwid    username\n        HWID-1  alice\ntrue            bob\n");
+    assert_eq!(table(&[json!("a"), json!(1)]), "a\n1\n");
+    assert_eq!(table(&[]), "");
+}
+
+#[test]
+fn license_list_filters() {
+    use keyauth::seller::stream::JsonStream;
+    let body = r#"{"success":true,"keys":[
+        {"id":"1","key":"AAA","note":null,"expires":"86400","status":"Not Used","level":"1","genby":"seller","gendate":"1640995200","usedon":null,"usedby":null,"app":"app","banned":null},
+        {"id":"2","key":"BBB","note":null,"expires":"86400","status":"Used","level":"1","genby":"seller","gendate":"1640995200","usedon":"1640995200","usedby":"bob","app":"app","banned":null},
+        {"id":"3","key":"CCC","note":null,"expires":"86400","status":"Used","level":"1","genby":"seller","gendate":"1640995200","usedon":"4102444800","usedby":"carol","app":"app","banned":null}
+    ]}"#;
+    let keys = |unused, expired, used_by| {
+        let licenses = select_licenses(JsonStream::new(body.as_bytes(), "keys"), unused, expired, used_by).unwrap();
+        licenses.into_iter().map(|l| l.key).collect::<Vec<_>>()
+    };
+    assert_eq!(keys(false, false, None), ["AAA", "BBB", "CCC"]);
+    assert_eq!(keys(true, false, None), ["AAA"]);
+    assert_eq!(keys(false, true, None), ["BBB"]);
+    assert_eq!(keys(false, false, Some("carol")), ["CCC"]);
+    assert!(keys(true, true, None).is_empty());
+
+    let none = JsonStream::new(r#"{"success":false,"message":"No keys found"}"#.as_bytes(), "keys");
+    assert_eq!(select_licenses(none, false, false, None), Ok(Vec::new()));
+    let other = JsonStream::new(r#"{"success":false,"message":"Seller key not found"}"#.as_bytes(), "keys");
+    assert_eq!(select_licenses(other, false, false, None), Err("Seller key not found".to_string()));
+}
+
+#[test]
+#[cfg(feature = "mock")]
+fn errors_and_empty_lists() {
+    use keyauth::seller::mock::{MockApp, MockServer, MOCK_SELLER_KEY};
+    let server = MockServer::start(MockApp::demo()).unwrap();
+    let url = server.url().to_string();
+    let args = |command: &[&str]| {
+        let mut args = vec!["--url", url.as_str(), "--seller-key", MOCK_SELLER_KEY, "--config", "/dev/null"];
+        args.extend_from_slice(command);
+        parse(&args).unwrap()
+    };
+    assert_eq!(run(&args(&["users", "reset-hwid", "nobody"])), Err("User not found".to_string()));
+    assert_eq!(run(&args(&["users", "ban", "bob"])), Ok(()));
+    assert!(server.app().users["bob"].banned.is_some());
+
+    // an empty app is an empty list and not an error
+    server.app().users.clear();
+    server.app().sessions.clear();
+    assert_eq!(run(&args(&["users", "list"])), Ok(()));
+    assert_eq!(run(&args(&["sessions", "list", "--json"])), Ok(()));
+}
@@ -0,0 +1,211 @@
+use std::fmt;
+use std::time::SystemTime;
+use serde::Deserialize;
+
+/// keyauth answered init with invalidver, the running version is outdated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateRequired {
+    /// download link set in the keyauth dashboard, can be empty
+    pub download_url: String,
+}
+
+impl UpdateRequired {
+    /// opens download_url in the default browser, this is what init used to do by itself
+    pub fn open_in_browser(&self) -> Result<(), String> {
+        if self.download_url.is_empty() {
+            return Err("no download url set".to_string());
+        }
+        webbrowser::open(&self.download_url).map_err(|e| e.to_string())
+    }
+}
+
+/// error returned by init
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InitError {
+    /// the version is outdated, see the updater module for replacing the running executable
+    UpdateRequired(UpdateRequired),
+    /// any other error, same messages as the other functions return
+    Other(String),
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitError::UpdateRequired(_) => f.write_str("invalidver"),
+            InitError::Other(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for InitError {}
+
+impl From<String> for InitError {
+    fn from(message: String) -> Self {
+        InitError::Other(message)
+    }
+}
+
+impl From<InitError> for String {
+    fn from(err: InitError) -> Self {
+        err.to_string()
+    }
+}
+
+/// a user from fetch_online
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct OnlineUser {
+    pub credential: String,
+}
+
+/// a message from get_chat
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ChatMessage {
+    pub author: String,
+    pub message: String,
+    #[serde(deserialize_with = "crate::fields::unix_timestamp")]
+    pub timestamp: SystemTime,
+}
+
+/// common interface of every api version's KeyauthApi, so code that talks to keyauth can be generic over the api version.
+/// every method just forwards to the method with the same name on the KeyauthApi struct of that version
+pub trait KeyauthClient {
+    /// initializes a session, **required to run before any other function!!!**
+    fn init(&mut self, hash: Option<&str>) -> Result<(), InitError>;
+    /// init with the [`crate::self_hash`] of the running executable, for apps with hash checking enabled
+    fn init_with_self_hash(&mut self) -> Result<(), InitError> {
+        let hash = crate::self_hash()?;
+        self.init(Some(&hash))
+    }
+    /// registeres a new user
+    fn register(&mut self, username: String, password: String, license: String, hwid: Option<String>) -> Result<(), String>;
+    /// upgrades a user license level or extends a license
+    fn upgrade(&mut self, username: String, license: String) -> Result<(), String>;
+    /// login self explanatory
+    fn login(&mut self, username: String, password: String, hwid: Option<String>) -> Result<(), String>;
+    /// <https://docs.keyauth.cc/api/license>
+    fn license(&mut self, license: String, hwid: Option<String>) -> Result<(), String>;
+    /// this will get a global variable (not user) and return it
+    fn var(&mut self, varid: String) -> Result<String, String>;
+    /// downloads a file, and decodes using base16::decode
+    fn file(&mut self, fileid: String) -> Result<Vec<u8>, String>;
+    /// sends a webhook from keyauth's servers so the url isnt exposed
+    fn webhook(&mut self, webid: String, params: String) -> Result<String, String>;
+    /// checks if the user is blacklisted and sets blacklisted acordingly
+    fn checkblacklist(&mut self) -> Result<(), String>;
+    /// checks if the session is still active or if it expired
+    fn check_session(&mut self) -> Result<bool, String>;
+    /// gets the list of online users
+    fn fetch_online(&mut self) -> Result<Vec<OnlineUser>, String>;
+    /// gets json of online users
+    fn fetch_online_raw(&mut self) -> Result<serde_json::Value, String>;
+    /// gets the messages in a channel
+    fn get_chat(&mut self, channel: String) -> Result<Vec<ChatMessage>, String>;
+    /// gets the arry of messages in a channel
+    fn get_chat_raw(&mut self, channel: String) -> Result<serde_json::Value, String>;
+    /// sends a chat message in a channel
+    fn send_chat_message(&mut self, channel: String, message: String) -> Result<(), String>;
+    /// sets a user variable to varvalue
+    fn setvar(&mut self, varname: String, varvalue: String) -> Result<(), String>;
+    /// gets a user variable
+    fn getvar(&mut self, varname: String) -> Result<String, String>;
+    /// logs somethink to keyauth
+    fn log(&mut self, message: String, pcuser: Option<String>);
+    /// self explanatory
+    fn ban(&mut self);
+    /// changes Username
+    fn change_username(&mut self, new_username: String) -> Result<String, String>;
+}
+
+/// implements KeyauthClient for a KeyauthApi by forwarding to its inherent methods
+macro_rules! impl_keyauth_client {
+    ($api:ty) => {
+        impl $crate::KeyauthClient for $api {
+            fn init(&mut self, hash: Option<&str>) -> Result<(), $crate::InitError> {
+                <$api>::init(self, hash)
+            }
+
+            fn register(&mut self, username: String, password: String, license: String, hwid: Option<String>) -> Result<(), String> {
+                <$api>::register(self, username, password, license, hwid)
+            }
+
+            fn upgrade(&mut self, username: String, license: String) -> Result<(), String> {
+                <$api>::upgrade(self, username, license)
+            }
+
+            fn login(&mut self, username: String, password: String, hwid: Option<String>) -> Result<(), String> {
+                <$api>::login(self, username, password, hwid)
+            }
+
+            fn license(&mut self, license: String, hwid: Option<String>) -> Result<(), String> {
+                <$api>::license(self, license, hwid)
+            }
+
+            fn var(&mut self, varid: String) -> Result<String, String> {
+                <$api>::var(self, varid)
+            }
+
+            fn file(&mut self, fileid: String) -> Result<Vec<u8>, String> {
+                <$api>::file(self, fileid)
+            }
+
+            fn webhook(&mut self, webid: String, params: String) -> Result<String, String> {
+                <$api>::webhook(self, webid, params)
+            }
+
+            fn checkblacklist(&mut self) -> Result<(), String> {
+                <$api>::checkblacklist(self)
+            }
+
+            fn check_session(&mut self) -> Result<bool, String> {
+                <$api>::check_session(self)
+            }
+
+            fn fetch_online(&mut self) -> Result<Vec<$crate::OnlineUser>, String> {
+                <$api>::fetch_online(self)
+            }
+
+            fn fetch_online_raw(&mut self) -> Result<serde_json::Value, String> {
+                <$api>::fetch_online_raw(self)
+            }
+
+            fn get_chat(&mut self, channel: String) -> Result<Vec<$crate::ChatMessage>, String> {
+                <$api>::get_chat(self, channel)
+            }
+
+            fn get_chat_raw(&mut self, channel: String) -> Result<serde_json::Value, String> {
+                <$api>::get_chat_raw(self, channel)
+            }
+
+            fn send_chat_message(&mut self, channel: String, message: String) -> Result<(), String> {
+                <$api>::send_chat_message(self, channel, message)
+            }
+
+            fn setvar(&mut self, varname: String, varvalue: String) -> Result<(), String> {
+                <$api>::setvar(self, varname, varvalue)
+            }
+
+            fn getvar(&mut self, varname: String) -> Result<String, String> {
+                <$api>::getvar(self, varname)
+            }
+
+            fn log(&mut self, message: String, pcuser: Option<String>) {
+                <$api>::log(self, message, pcuser)
+            }
+
+            fn ban(&mut self) {
+                <$api>::ban(self)
+            }
+
+            fn change_username(&mut self, new_username: String) -> Result<String, String> {
+                <$api>::change_username(self, new_username)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "v1_0")]
+impl_keyauth_client!(crate::v1_0::KeyauthApi);
+#[cfg(feature = "v1_1")]
+impl_keyauth_client!(crate::v1_1::KeyauthApi);
+#[cfg(feature = "v1_2")]
+impl_keyauth_client!(crate::v1_2::KeyauthApi);
@@ -0,0 +1,97 @@
+//! serde helpers for keyauth's loosely typed fields, numbers and timestamps come as strings or numbers depending on the endpoint
+// most of these are only used by the seller api
+#![cfg_attr(not(feature = "seller"), allow(dead_code))]
+
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Deserializer, Serializer};
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrNumber {
+    Number(u64),
+    String(String),
+}
+
+/// a number that can also be sent as a string
+pub(crate) fn number<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr + TryFrom<u64>,
+    <T as FromStr>::Err: Display,
+    <T as TryFrom<u64>>::Error: Display,
+{
+    match StringOrNumber::deserialize(deserializer)? {
+        StringOrNumber::Number(n) => T::try_from(n).map_err(serde::de::Error::custom),
+        StringOrNumber::String(s) => s.trim().parse().map_err(serde::de::Error::custom),
+    }
+}
+
+/// unix seconds
+pub(crate) fn unix_timestamp<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+    let secs: u64 = number(deserializer)?;
+    Ok(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// unix seconds, null, "" or 0 are None
+pub(crate) fn optional_unix_timestamp<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<SystemTime>, D::Error> {
+    let secs = match Option::<StringOrNumber>::deserialize(deserializer)? {
+        None => return Ok(None),
+        Some(StringOrNumber::Number(n)) => n,
+        Some(StringOrNumber::String(s)) if s.trim().is_empty() => return Ok(None),
+        Some(StringOrNumber::String(s)) => s.trim().parse().map_err(serde::de::Error::custom)?,
+    };
+    Ok((secs != 0).then(|| UNIX_EPOCH + Duration::from_secs(secs)))
+}
+
+/// a duration in seconds
+pub(crate) fn seconds<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    number(deserializer).map(Duration::from_secs)
+}
+
+/// a string where keyauth uses null, "" or "0" for nothing
+pub(crate) fn optional_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    let value = Option::<StringOrNumber>::deserialize(deserializer)?;
+    Ok(match value {
+        Some(StringOrNumber::String(s)) if !s.is_empty() && s != "0" => Some(s),
+        _ => None,
+    })
+}
+
+/// a bool that can also be sent as 0/1 or "0"/"1"
+pub(crate) fn flag<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flag {
+        Bool(bool),
+        Number(u64),
+        String(String),
+    }
+    match Option::<Flag>::deserialize(deserializer)? {
+        None => Ok(false),
+        Some(Flag::Bool(b)) => Ok(b),
+        Some(Flag::Number(n)) => Ok(n != 0),
+        Some(Flag::String(s)) => match s.trim() {
+            "" | "0" | "false" => Ok(false),
+            "1" | "true" => Ok(true),
+            other => Err(serde::de::Error::custom(format!("invalid flag {:?}", other))),
+        },
+    }
+}
+
+pub(crate) fn serialize_unix_timestamp<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+    let secs = time.duration_since(UNIX_EPOCH).map_err(serde::ser::Error::custom)?.as_secs();
+    serializer.serialize_u64(secs)
+}
+
+pub(crate) fn serialize_optional_unix_timestamp<S: Serializer>(time: &Option<SystemTime>, serializer: S) -> Result<S::Ok, S::Error> {
+    match time {
+        Some(time) => serialize_unix_timestamp(time, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub(crate) fn serialize_seconds<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_secs())
+}
@@ -12,7 +12,7 @@ keyauth = { version = "*", features = ["v1_1", "seller"], default-features = fal
 the ``default-features = false`` disabled the default v1_2 api.
 
 basic usage:
-```rust
+```rust,no_run
 let mut auth = keyauth::v1_2::KeyauthApi::new("application name", "ownerid", "application secret", "application version", "api url"); // if you dont have a custom domain for api use "https://keyauth.win/api/1.2/"
 auth.init(None).unwrap(); // None -> no hash set, Some("hash") -> if you have has checking enabled
 auth.login("username".to_string(), "password".to_string(), Some("hwid".to_string())).unwrap(); // if you want to automaticly generate hwid use None insted of Some(...)
@@ -20,9 +20,32 @@ auth.login("username".to_string(), "password".to_string(), Some("hwid".to_string
 
 also if you want to use an obfuscator for rust i recommend using [obfstr](https://crates.io/crates/obfstr) and [llvm obfuscator](https://github.com/eshard/obfuscator-llvm/wiki/Rust-obfuscation-guide)
 
+with the obfuscate feature `keyauth::app!{ name: .., ownerid: .., secret: .., version: .. }` creates the v1_2 api with these encrypted at compile time, so they arent in the binary as plain text and the secret is only decrypted while requests are signed
+
 if the panic feature is enabled then the v1_2 api will panic insted of returning an error when it detects that the request was tampered with
+
+if the tracing feature is enabled every v1_2 and seller request gets a `keyauth` [tracing](https://crates.io/crates/tracing) span with the request type, http status, success flag, signature check and latency, secrets like the app secret, seller key, passwords and license keys are redacted from it
+
+every api version implements the [`KeyauthClient`] trait, so application code can be written once and the api version picked with a feature flag:
+```rust,no_run
+fn login<C: keyauth::KeyauthClient>(auth: &mut C) -> Result<(), String> {
+    auth.init(None)?;
+    auth.login("username".to_string(), "password".to_string(), None)
+}
+```
 */
 
+use machineid_rs::{IdBuilder, Encryption, HWIDComponent};
+
+mod client;
+mod fields;
+mod rate_limit;
+mod secret;
+mod trace;
+pub use client::{KeyauthClient, OnlineUser, ChatMessage, InitError, UpdateRequired};
+pub use rate_limit::RateLimiter;
+pub use secret::{Sealed, Secret};
+
 #[cfg(feature = "v1_0")]
 pub mod v1_0;
 #[cfg(feature = "v1_1")]
@@ -31,6 +54,45 @@ pub mod v1_1;
 pub mod v1_2;
 #[cfg(feature = "seller")]
 pub mod seller;
+#[cfg(feature = "updater")]
+pub mod updater;
+pub mod metrics;
+#[cfg(feature = "obfuscate")]
+#[doc(hidden)]
+pub mod obfuscate;
+
+pub(crate) fn get_hwid() -> String {
+    let mut builder = IdBuilder::new(Encryption::SHA256);
+    builder
+        .add_component(HWIDComponent::SystemID)
+        .add_component(HWIDComponent::CPUCores);
+
+    builder.build("mykey").unwrap()
+}
+
+/// md5 of a file as lowercase hex, this is the hash keyauth checks when hash checking is enabled
+pub fn file_hash<P: AsRef<std::path::Path>>(path: P) -> Result<String, String> {
+    use md5::{Digest, Md5};
+
+    let mut file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut hasher = Md5::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| e.to_string())?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// [`file_hash`] of the running executable, pass it to init (or use [`KeyauthClient::init_with_self_hash`])
+pub fn self_hash() -> Result<String, String> {
+    #[cfg(target_os = "linux")]
+    {
+        file_hash("/proc/self/exe")
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        file_hash(std::env::current_exe().map_err(|e| e.to_string())?)
+    }
+}
 
 #[cfg(test)]
+// the bench tests ignore the results of init and login on purpose
+#[allow(unused_must_use)]
 mod test;
@@ -0,0 +1,187 @@
+/*!
+request metrics for the v1_2 api. set [`KeyauthApi::metrics`](crate::v1_2::KeyauthApi::metrics) to anything that implements [`Metrics`],
+[`InMemoryMetrics`] keeps counters and latency histograms and renders them in the prometheus text format
+
+```rust,no_run
+use std::sync::Arc;
+use keyauth::metrics::InMemoryMetrics;
+let metrics = Arc::new(InMemoryMetrics::new());
+let mut auth = keyauth::v1_2::KeyauthApi::new("name", "ownerid", "secret", "1.0", "https://keyauth.win/api/1.2/");
+auth.metrics = Some(metrics.clone());
+auth.init(None).unwrap();
+// serve this on /metrics
+println!("{}", metrics.render());
+```
+*/
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// why a request didnt get an answer from keyauth
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorKind {
+    /// the request didnt reach keyauth or the response couldnt be read (or, for files, written out)
+    Network,
+    /// the response signature was missing or wrong
+    Tampered,
+    /// keyauth doesnt know the application
+    InvalidApplication,
+    /// the response isnt the json that was expected
+    InvalidResponse,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ErrorKind::Network => "network",
+            ErrorKind::Tampered => "tampered",
+            ErrorKind::InvalidApplication => "invalid_application",
+            ErrorKind::InvalidResponse => "invalid_response",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Outcome {
+    /// keyauth answered with success true, for files also the signature and sha256 matched.
+    /// log and ban count as success when the answer isnt json
+    Success,
+    /// keyauth answered with success false, e.g. a wrong password
+    Failed,
+    Error(ErrorKind),
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Success => f.write_str("success"),
+            Outcome::Failed => f.write_str("failed"),
+            Outcome::Error(_) => f.write_str("error"),
+        }
+    }
+}
+
+/// one finished request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestEvent<'a> {
+    /// api version, e.g. "1.2"
+    pub api: &'a str,
+    /// the type parameter, e.g. "init" or "login"
+    pub action: &'a str,
+    pub latency: Duration,
+    pub outcome: Outcome,
+}
+
+/// receives an event for every request, called on the thread that made the request so it should be quick
+pub trait Metrics: Send + Sync + fmt::Debug {
+    fn record(&self, event: &RequestEvent);
+}
+
+/// upper bounds of the latency histogram buckets in seconds
+pub const LATENCY_BUCKETS: [f64; 10] = [0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+#[derive(Debug, Default)]
+struct Histogram {
+    /// per bucket, not cumulative
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    /// (api, action, outcome)
+    requests: BTreeMap<(String, String, String), u64>,
+    /// (api, action, kind)
+    errors: BTreeMap<(String, String, ErrorKind), u64>,
+    /// (api, action)
+    tampered: BTreeMap<(String, String), u64>,
+    latency: BTreeMap<(String, String), Histogram>,
+}
+
+/// counters and latency histograms in memory, share it with an Arc and call render for the prometheus endpoint
+#[derive(Debug, Default)]
+pub struct InMemoryMetrics {
+    counters: Mutex<Counters>,
+}
+
+impl InMemoryMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// number of requests of an action with an outcome so far
+    pub fn requests(&self, action: &str, outcome: Outcome) -> u64 {
+        let counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        match outcome {
+            Outcome::Error(kind) => counters.errors.iter().filter(|((_, a, k), _)| a == action && *k == kind).map(|(_, n)| n).sum(),
+            _ => {
+                let outcome = outcome.to_string();
+                counters.requests.iter().filter(|((_, a, o), _)| a == action && *o == outcome).map(|(_, n)| n).sum()
+            }
+        }
+    }
+
+    /// everything in the prometheus text exposition format
+    pub fn render(&self) -> String {
+        let counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = String::new();
+        let _ = writeln!(out, "# HELP keyauth_requests_total keyauth requests by action and outcome");
+        let _ = writeln!(out, "# TYPE keyauth_requests_total counter");
+        for ((api, action, outcome), n) in &counters.requests {
+            let _ = writeln!(out, "keyauth_requests_total{{api=\"{}\",action=\"{}\",outcome=\"{}\"}} {}", escape(api), escape(action), outcome, n);
+        }
+        let _ = writeln!(out, "# HELP keyauth_errors_total keyauth requests without a valid answer by kind");
+        let _ = writeln!(out, "# TYPE keyauth_errors_total counter");
+        for ((api, action, kind), n) in &counters.errors {
+            let _ = writeln!(out, "keyauth_errors_total{{api=\"{}\",action=\"{}\",kind=\"{}\"}} {}", escape(api), escape(action), kind, n);
+        }
+        let _ = writeln!(out, "# HELP keyauth_tamper_detections_total responses with a missing or wrong signature");
+        let _ = writeln!(out, "# TYPE keyauth_tamper_detections_total counter");
+        for ((api, action), n) in &counters.tampered {
+            let _ = writeln!(out, "keyauth_tamper_detections_total{{api=\"{}\",action=\"{}\"}} {}", escape(api), escape(action), n);
+        }
+        let _ = writeln!(out, "# HELP keyauth_request_duration_seconds keyauth request latency");
+        let _ = writeln!(out, "# TYPE keyauth_request_duration_seconds histogram");
+        for ((api, action), histogram) in &counters.latency {
+            let labels = format!("api=\"{}\",action=\"{}\"", escape(api), escape(action));
+            let mut cumulative = 0;
+            for (le, n) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+                cumulative += n;
+                let _ = writeln!(out, "keyauth_request_duration_seconds_bucket{{{},le=\"{}\"}} {}", labels, le, cumulative);
+            }
+            let _ = writeln!(out, "keyauth_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}", labels, histogram.count);
+            let _ = writeln!(out, "keyauth_request_duration_seconds_sum{{{}}} {}", labels, histogram.sum);
+            let _ = writeln!(out, "keyauth_request_duration_seconds_count{{{}}} {}", labels, histogram.count);
+        }
+        out
+    }
+}
+
+impl Metrics for InMemoryMetrics {
+    fn record(&self, event: &RequestEvent) {
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        let (api, action) = (event.api.to_string(), event.action.to_string());
+        *counters.requests.entry((api.clone(), action.clone(), event.outcome.to_string())).or_default() += 1;
+        if let Outcome::Error(kind) = event.outcome {
+            *counters.errors.entry((api.clone(), action.clone(), kind)).or_default() += 1;
+            if kind == ErrorKind::Tampered {
+                *counters.tampered.entry((api.clone(), action.clone())).or_default() += 1;
+            }
+        }
+        let histogram = counters.latency.entry((api, action)).or_default();
+        let secs = event.latency.as_secs_f64();
+        if let Some(i) = LATENCY_BUCKETS.iter().position(|&le| secs <= le) {
+            histogram.buckets[i] += 1;
+        }
+        histogram.sum += secs;
+        histogram.count += 1;
+    }
+}
+
+/// label values have \, " and newlines escaped
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
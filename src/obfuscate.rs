@@ -0,0 +1,51 @@
+//! compile time encryption of the app constants for the obfuscate feature, see [`app!`](crate::app)
+
+#[doc(hidden)]
+pub use const_random::const_random;
+#[doc(hidden)]
+pub use crate::secret::seal;
+
+/// a v1_2 [`KeyauthApi`](crate::v1_2::KeyauthApi) whose name, ownerid, secret and version are encrypted at compile time,
+/// so none of them is in the binary as plain text. the secret stays encrypted in the client and is only decrypted
+/// for a moment to sign and check requests, name, ownerid and version are decrypted once since they are sent with every request.
+/// url is optional and defaults to `https://keyauth.win/api/1.2/`, every value has to be a string literal or a const
+/// ```rust,no_run
+/// let mut auth = keyauth::app! {
+///     name: "application name",
+///     ownerid: "ownerid",
+///     secret: "application secret",
+///     version: "1.0",
+/// };
+/// auth.init(None).unwrap();
+/// ```
+#[macro_export]
+macro_rules! app {
+    (name: $name:expr, ownerid: $ownerid:expr, secret: $secret:expr, version: $version:expr $(,)?) => {
+        $crate::app!(name: $name, ownerid: $ownerid, secret: $secret, version: $version, url: "https://keyauth.win/api/1.2/")
+    };
+    (name: $name:expr, ownerid: $ownerid:expr, secret: $secret:expr, version: $version:expr, url: $url:expr $(,)?) => {
+        $crate::v1_2::KeyauthApi::with_sealed_secret(
+            &$crate::sealed!($name).reveal(),
+            &$crate::sealed!($ownerid).reveal(),
+            $crate::sealed!($secret),
+            &$crate::sealed!($version).reveal(),
+            $url,
+        )
+    };
+}
+
+/// a [`Sealed`](crate::Sealed) encrypted at compile time with a random key, value has to be a string literal or a const
+/// ```rust
+/// let seller_key = keyauth::sealed!("sellerkey");
+/// assert_eq!(&*seller_key.reveal(), "sellerkey");
+/// ```
+#[macro_export]
+macro_rules! sealed {
+    ($value:expr) => {{
+        // only used at compile time, so the plain text doesnt end up in the binary
+        const VALUE: &str = $value;
+        const KEY: u64 = $crate::obfuscate::const_random!(u64);
+        static SEALED: [u8; VALUE.len()] = $crate::obfuscate::seal(VALUE, KEY);
+        $crate::Sealed::from_static(&SEALED, KEY)
+    }};
+}
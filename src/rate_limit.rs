@@ -0,0 +1,138 @@
+//! token bucket rate limiting for the client and seller apis
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use reqwest::StatusCode;
+use reqwest::blocking::Response;
+
+/// waits for a token before every request and backs off when keyauth answers with 429 too many requests.
+/// clones share the same bucket, so one limiter can be given to several api and seller clients
+/// (`SellerClient::with_rate_limiter`) to keep all of them under one limit
+/// ```rust,no_run
+/// let limiter = keyauth::RateLimiter::new(5.0, 10);
+/// let mut auth = keyauth::v1_2::KeyauthApi::new("name", "ownerid", "secret", "1.0", "https://keyauth.win/api/1.2/");
+/// auth.rate_limiter = Some(limiter.clone());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    bucket: Arc<Mutex<Bucket>>,
+    max_retries: u32,
+    max_backoff: Duration,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    per_second: f64,
+    burst: f64,
+    tokens: f64,
+    last: Instant,
+    /// set after a throttled response, nobody sharing the limiter sends before it
+    blocked_until: Option<Instant>,
+}
+
+impl Bucket {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.burst);
+        self.last = now;
+    }
+
+    /// takes a token or returns how long to wait for one
+    fn take(&mut self, now: Instant) -> Result<(), Duration> {
+        if let Some(until) = self.blocked_until {
+            if until > now {
+                return Err(until - now);
+            }
+            self.blocked_until = None;
+        }
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64((1.0 - self.tokens) / self.per_second))
+    }
+}
+
+impl RateLimiter {
+    /// per_second requests on average, up to burst at once after being idle.
+    /// throttled requests are retried 5 times with at most 60 seconds between them
+    pub fn new(per_second: f64, burst: u32) -> Self {
+        assert!(per_second > 0.0, "per_second has to be positive");
+        let burst = burst.max(1) as f64;
+        Self {
+            bucket: Arc::new(Mutex::new(Bucket {
+                per_second,
+                burst,
+                tokens: burst,
+                last: Instant::now(),
+                blocked_until: None,
+            })),
+            max_retries: 5,
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+
+    /// how often a throttled request is sent again before the 429 response is returned
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// longest wait after a throttled response, Retry-After is capped to this too
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// blocks until a request may be sent
+    pub fn acquire(&self) {
+        loop {
+            let wait = match self.bucket.lock().unwrap_or_else(|e| e.into_inner()).take(Instant::now()) {
+                Ok(()) => return,
+                Err(wait) => wait,
+            };
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// takes a token if one is there without waiting
+    pub fn try_acquire(&self) -> bool {
+        self.bucket.lock().unwrap_or_else(|e| e.into_inner()).take(Instant::now()).is_ok()
+    }
+
+    /// blocks everyone sharing the limiter for the backoff of a throttled response and empties the bucket
+    fn throttled(&self, retry_after: Option<Duration>, attempt: u32) {
+        let backoff = retry_after
+            .unwrap_or_else(|| Duration::from_secs(1) * 2u32.saturating_pow(attempt))
+            .min(self.max_backoff);
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let until = Instant::now() + backoff;
+        let until = bucket.blocked_until.map_or(until, |b| b.max(until));
+        bucket.blocked_until = Some(until);
+        // no tokens pile up while blocked
+        bucket.tokens = 0.0;
+        bucket.last = until;
+    }
+
+    /// sends a request built by send, waiting for tokens and retrying throttled responses
+    pub(crate) fn send<F>(&self, mut send: F) -> reqwest::Result<Response>
+    where
+        F: FnMut() -> reqwest::Result<Response>,
+    {
+        let mut attempt = 0;
+        loop {
+            self.acquire();
+            let response = send()?;
+            if response.status() != StatusCode::TOO_MANY_REQUESTS || attempt >= self.max_retries {
+                return Ok(response);
+            }
+            let retry_after = response.headers().get("retry-after")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .map(Duration::from_secs);
+            self.throttled(retry_after, attempt);
+            attempt += 1;
+        }
+    }
+}
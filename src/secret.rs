@@ -0,0 +1,141 @@
+//! strings that are wiped from memory when dropped and never show up in debug output
+
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Deref;
+use zeroize::{Zeroize, Zeroizing};
+
+/// holds app secrets, seller keys, encryption keys and session ids.
+/// the memory is zeroed when it is dropped or replaced, `{:?}` prints `<redacted>` (or `""` when empty, to tell if it is set)
+/// and there is no Display so it cant end up in a format string by accident, use as_str to get the value
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret(Zeroizing<String>);
+
+impl Secret {
+    pub fn new(value: String) -> Self {
+        Self(Zeroizing::new(value))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Deref for Secret {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self::new(value.to_string())
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            f.write_str("\"\"")
+        } else {
+            f.write_str("<redacted>")
+        }
+    }
+}
+
+/// a string kept xor encrypted in memory and only decrypted while it is used, the app secret of the v1_2 api is kept like this.
+/// made with [`app!`](crate::app) it is encrypted at compile time too so it isnt in the binary as plain text.
+/// this only keeps it away from `strings` and memory scanners, the key is right next to it so it doesnt stop a debugger
+#[derive(Clone, Default)]
+pub struct Sealed {
+    bytes: Cow<'static, [u8]>,
+    key: u64,
+}
+
+impl Sealed {
+    /// encrypts value with a random key
+    pub fn new(value: &str) -> Self {
+        let key = uuid::Uuid::new_v4().as_u64_pair().0;
+        let mut bytes = value.as_bytes().to_vec();
+        apply(&mut bytes, key);
+        Self { bytes: Cow::Owned(bytes), key }
+    }
+
+    /// bytes encrypted at compile time with [`seal`], used by app!
+    #[doc(hidden)]
+    pub const fn from_static(bytes: &'static [u8], key: u64) -> Self {
+        Self { bytes: Cow::Borrowed(bytes), key }
+    }
+
+    /// a decrypted copy that is zeroed when dropped, keep it only as long as it is needed
+    pub fn reveal(&self) -> Zeroizing<String> {
+        let mut bytes = Zeroizing::new(self.bytes.to_vec());
+        apply(&mut bytes, self.key);
+        // only ever made from a str
+        Zeroizing::new(String::from_utf8(std::mem::take(&mut *bytes)).unwrap_or_default())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+}
+
+impl Drop for Sealed {
+    fn drop(&mut self) {
+        if let Cow::Owned(bytes) = &mut self.bytes {
+            bytes.zeroize();
+        }
+        self.key.zeroize();
+    }
+}
+
+impl fmt::Debug for Sealed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            f.write_str("\"\"")
+        } else {
+            f.write_str("<redacted>")
+        }
+    }
+}
+
+/// splitmix64, the keystream byte for position i is taken from the i / 8th output
+const fn keystream(key: u64, block: u64) -> u64 {
+    let mut z = key.wrapping_add(block.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn apply(bytes: &mut [u8], key: u64) {
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte ^= (keystream(key, i as u64 / 8) >> ((i % 8) * 8)) as u8;
+    }
+}
+
+/// encrypts value for [`Sealed::from_static`] at compile time, N has to be the length of value
+#[cfg(feature = "obfuscate")]
+#[doc(hidden)]
+pub const fn seal<const N: usize>(value: &str, key: u64) -> [u8; N] {
+    let value = value.as_bytes();
+    assert!(value.len() == N, "sealed value has the wrong length");
+    let mut bytes = [0u8; N];
+    let mut i = 0;
+    while i < N {
+        bytes[i] = value[i] ^ (keystream(key, i as u64 / 8) >> ((i % 8) * 8)) as u8;
+        i += 1;
+    }
+    bytes
+}
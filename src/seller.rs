@@ -1,1332 +1,1831 @@
+/*!
+the seller api, through [`SellerClient`] or the free functions that take the sellerkey and url on every call.
+
+an Err is keyauth's message as plain text, e.g. `User not found`. older versions of this crate returned it
+json encoded with the quotes (`"User not found"`), code that stripped them can stop doing that
+*/
+
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use reqwest::blocking::{Client};
 use serde_json::Value;
 
-pub(crate) fn request(req_data: HashMap<&str, &str>, url: String) -> reqwest::blocking::Response {
-    let client = Client::new();
-    client.get(url)
-        .query(&req_data)
-        .header("User-Agent", "KeyAuth")
-        .send().unwrap()
+/// seller api client, keeps the sellerkey, url and http client so they dont have to be passed to every call.
+/// the endpoints are grouped like the docs, e.g. `client.licenses().create(..)` or `client.users().ban(..)`
+#[derive(Clone)]
+pub struct SellerClient {
+    key: crate::Secret,
+    url: String,
+    http: Client,
+    limiter: Option<crate::RateLimiter>,
+    retry: Option<Retry>,
+}
+
+#[derive(Debug, Clone)]
+struct Retry {
+    retries: u32,
+    delay: Duration,
+    retried: Arc<AtomicU32>,
 }
 
+// the sellerkey is never printed
+impl std::fmt::Debug for SellerClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SellerClient")
+            .field("key", &self.key)
+            .field("url", &self.url)
+            .field("limiter", &self.limiter)
+            .field("retry", &self.retry)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SellerClient {
+    /// url is the seller api url, e.g. `https://keyauth.win/api/seller/`
+    pub fn new(key: &str, url: String) -> Self {
+        Self::with_http(key, url, Client::new())
+    }
+
+    /// same as new but with your own reqwest client, e.g. for proxies or timeouts
+    pub fn with_http(key: &str, url: String, http: Client) -> Self {
+        Self { key: key.into(), url, http, limiter: None, retry: None }
+    }
+
+    /// waits for the limiter before every request and backs off when keyauth throttles instead of failing,
+    /// the limiter can be shared with other seller clients and the client api
+    pub fn with_rate_limiter(mut self, limiter: crate::RateLimiter) -> Self {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    /// repeats a request up to retries times when the connection to keyauth couldnt be made,
+    /// waiting delay before the first retry and twice as long before every further one.
+    /// timeouts and connections lost after the request was sent arent retried because keyauth may already have
+    /// handled the request, neither are errors keyauth answers with
+    pub fn with_retries(mut self, retries: u32, delay: Duration) -> Self {
+        self.retry = Some(Retry { retries, delay, retried: Arc::new(AtomicU32::new(0)) });
+        self
+    }
+
+    /// how many requests were sent again because of with_retries, shared between clones
+    pub fn retried(&self) -> u32 {
+        self.retry.as_ref().map_or(0, |r| r.retried.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn request(&self, req_data: HashMap<&str, &str>) -> Result<Response, String> {
+        let mut call = crate::trace::Call::start("seller", req_data.get("type").copied().unwrap_or_default(), &req_data);
+        let send = || self.http.get(&self.url)
+            .query(&req_data)
+            .header("User-Agent", "KeyAuth")
+            .send();
+        let mut attempt = 0;
+        let result = loop {
+            let result = match &self.limiter {
+                Some(limiter) => limiter.send(send),
+                None => send(),
+            };
+            // only a failed connect is sure to not have reached keyauth
+            match (&result, &self.retry) {
+                (Err(err), Some(retry)) if err.is_connect() && attempt < retry.retries => {
+                    std::thread::sleep(retry.delay * 2u32.saturating_pow(attempt));
+                    retry.retried.fetch_add(1, Ordering::Relaxed);
+                    attempt += 1;
+                }
+                _ => break result,
+            }
+        };
+        let inner = match result {
+            Ok(inner) => inner,
+            Err(err) => {
+                let err = err.to_string();
+                call.error(&err);
+                return Err(err);
+            }
+        };
+        call.status(inner.status().as_u16());
+        Ok(Response { inner, call })
+    }
+
+    /// sends the request and returns the response, Err with keyauth's message if success is false
+    pub(crate) fn json(&self, req_data: HashMap<&str, &str>) -> Result<Value, String> {
+        let resp = self.request(req_data)?.text().map_err(|e| e.to_string())?;
+        let json: Value = serde_json::from_str(&resp).map_err(|e| e.to_string())?;
+        if !json["success"].as_bool().unwrap_or(false) {
+            return Err(text(&json["message"]));
+        }
+        Ok(json)
+    }
+
+    /// for the endpoints that only answer with a message
+    pub(crate) fn message(&self, req_data: HashMap<&str, &str>) -> Result<String, String> {
+        self.json(req_data).map(|json| text(&json["message"]))
+    }
+
+    /// sends a fetch request of type ty and streams the array called key out of the response
+    pub(crate) fn stream<T: serde::de::DeserializeOwned>(&self, ty: &str, key: &str) -> stream::JsonStream<T> {
+        let mut req_data = HashMap::new();
+        req_data.insert("sellerkey", self.key.as_str());
+        req_data.insert("type", ty);
+        match self.request(req_data) {
+            Ok(response) => stream::JsonStream::new(response, key),
+            Err(err) => stream::JsonStream::failed(err),
+        }
+    }
+
+    /// same as stream but collects the items, a string instead of the list means there are none
+    pub(crate) fn fetch_list<T: serde::de::DeserializeOwned>(&self, ty: &str, key: &str) -> Result<Vec<T>, String> {
+        self.stream(ty, key).collect()
+    }
+
+    pub fn licenses(&self) -> licenses::LicensesApi<'_> {
+        licenses::LicensesApi { client: self }
+    }
+
+    pub fn users(&self) -> user::UserApi<'_> {
+        user::UserApi { client: self }
+    }
+
+    pub fn subscriptions(&self) -> subscriptions::SubscriptionsApi<'_> {
+        subscriptions::SubscriptionsApi { client: self }
+    }
+
+    pub fn chat(&self) -> chat::ChatApi<'_> {
+        chat::ChatApi { client: self }
+    }
+
+    pub fn sessions(&self) -> sessions::SessionsApi<'_> {
+        sessions::SessionsApi { client: self }
+    }
+
+    pub fn files(&self) -> files::FilesApi<'_> {
+        files::FilesApi { client: self }
+    }
+
+    pub fn variables(&self) -> variables::VariablesApi<'_> {
+        variables::VariablesApi { client: self }
+    }
+
+    pub fn blacklists(&self) -> blacklists::BlacklistsApi<'_> {
+        blacklists::BlacklistsApi { client: self }
+    }
+
+    pub fn settings(&self) -> settings::SettingsApi<'_> {
+        settings::SettingsApi { client: self }
+    }
+
+    pub fn account(&self) -> account::AccountApi<'_> {
+        account::AccountApi { client: self }
+    }
+
+    pub fn web_loader(&self) -> web_loader::WebLoaderApi<'_> {
+        web_loader::WebLoaderApi { client: self }
+    }
+
+    /// creates a webhook, returns the webhook id
+    pub fn webhook_create(&self, baseurl: &str, user_agent: &str, authed: Option<bool>) -> Result<String, String> {
+        let mut req_data = HashMap::new();
+        req_data.insert("sellerkey", self.key.as_str());
+        req_data.insert("type", "addwebhook");
+        req_data.insert("baseurl", baseurl);
+        req_data.insert("ua", user_agent);
+        let authed = authed.unwrap_or(false);
+        let authed = if authed { "1" } else { "0" };
+        req_data.insert("authed", authed);
+
+        self.message(req_data)
+    }
+}
+
+/// a string field of a response without the json quotes, other values as json
+pub(crate) fn text(value: &Value) -> String {
+    value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string())
+}
+
+/// keyauth answers a fetch of an empty list with success false and a message like "No users found",
+/// this turns that answer into an empty list and keeps every other error
+pub fn empty_if_none_found<T>(result: Result<Vec<T>, String>) -> Result<Vec<T>, String> {
+    match result {
+        Err(message) if is_none_found(&message) => Ok(Vec::new()),
+        other => other,
+    }
+}
+
+/// if message is keyauth's answer for an empty list
+pub fn is_none_found(message: &str) -> bool {
+    let message = message.trim().to_ascii_lowercase();
+    message.starts_with("no ") && message.ends_with(" found")
+}
+
+/// body of a seller api response, the trace span of the request ends when it is dropped
+pub(crate) struct Response {
+    inner: reqwest::blocking::Response,
+    call: crate::trace::Call,
+}
+
+impl Response {
+    pub(crate) fn text(mut self) -> std::io::Result<String> {
+        let mut text = String::new();
+        std::io::Read::read_to_string(&mut self, &mut text)?;
+        Ok(text)
+    }
+}
+
+impl std::io::Read for Response {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.call.scan(&buf[..n]);
+        Ok(n)
+    }
+}
+
+pub mod stream;
+pub mod transfer;
+pub mod backup;
+pub mod bulk;
+#[cfg(feature = "config")]
+pub mod config;
+#[cfg(feature = "mock")]
+pub mod mock;
+
 /// https://docs.keyauth.cc/seller/licenses
 pub mod licenses {
     use std::collections::HashMap;
-    use reqwest::StatusCode;
-    use serde_json::Value;
+    use std::time::{Duration, SystemTime};
+
+    /// the most keys keyauth generates in one request, [`LicensesApi::create_bulk`] splits bigger amounts into batches of this size
+    pub const MAX_PER_REQUEST: u32 = 100;
+
+    /// characters used for the random part of the mask
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum Charset {
+        /// mixed case, keyauth's default
+        #[default]
+        Random,
+        Uppercase,
+        Lowercase,
+    }
+
+    impl Charset {
+        fn as_param(self) -> &'static str {
+            match self {
+                Charset::Random => "1",
+                Charset::Uppercase => "2",
+                Charset::Lowercase => "3",
+            }
+        }
+    }
 
-    /// returns a list of licenses, if errors returns a response message
-    pub fn create(sellerkey: &str, url: String, expiry: u64, mask: Option<String>, level: Option<i32>, amount: Option<u8>, owner: Option<String>) -> Result<Vec<String>, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "add");
-        req_data.insert("sellerkey", &sellerkey);
-        let expiry = expiry.to_string();
-        req_data.insert("expiry", expiry.as_str());
-        let mask = match mask {
-            Some(m) => m,
-            None => "XXXXXX-XXXXXX-XXXXXX-XXXXXX-XXXXXX-XXXXXX".to_string(),
-        };
-        req_data.insert("mask", mask.as_str());
-        let level = match level {
-            Some(l) => l,
-            None => 1,
-        };
-        let level = level.to_string();
-        req_data.insert("level", level.as_str());
-        let amount = match amount {
-            Some(a) => a,
-            None => 1,
-        };
-        let amount = amount.to_string();
-        req_data.insert("amount", amount.as_str());
-        let owner = match owner {
-            Some(o) => o,
-            None => "none".to_string(),
-        };
-        if owner != "none" {
-            req_data.insert("owner", owner.as_str());
+    /// parameters for creating licenses, validated before anything is sent
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use keyauth::seller::{SellerClient, licenses::LicenseSpec};
+    /// let client = SellerClient::new("sellerkey", "https://keyauth.win/api/seller/".to_string());
+    /// let spec = LicenseSpec::new(Duration::from_secs(30 * 86400)).mask("PRO-****-****").amount(2500).note("giveaway");
+    /// let created = client.licenses().create_bulk(&spec).unwrap();
+    /// println!("{} keys, {} failed", created.keys.len(), created.failed());
+    /// ```
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct LicenseSpec {
+        pub expiry: Duration,
+        pub mask: String,
+        pub level: u32,
+        pub amount: u32,
+        pub owner: Option<String>,
+        pub note: Option<String>,
+        pub charset: Charset,
+    }
+
+    impl LicenseSpec {
+        /// one level 1 key with the default mask, expiry is rounded to whole seconds and has to be at least a second
+        pub fn new(expiry: Duration) -> Self {
+            Self {
+                expiry,
+                mask: "XXXXXX-XXXXXX-XXXXXX-XXXXXX-XXXXXX-XXXXXX".to_string(),
+                level: 1,
+                amount: 1,
+                owner: None,
+                note: None,
+                charset: Charset::default(),
+            }
+        }
+
+        /// `*` and `X` are replaced with random characters, anything else alphanumeric or `-` is kept as is
+        pub fn mask(mut self, mask: impl Into<String>) -> Self {
+            self.mask = mask.into();
+            self
+        }
+
+        pub fn level(mut self, level: u32) -> Self {
+            self.level = level;
+            self
+        }
+
+        /// how many keys to create, more than [`MAX_PER_REQUEST`] needs [`LicensesApi::create_bulk`]
+        pub fn amount(mut self, amount: u32) -> Self {
+            self.amount = amount;
+            self
+        }
+
+        /// the user the keys are credited to in the dashboard
+        pub fn owner(mut self, owner: impl Into<String>) -> Self {
+            self.owner = Some(owner.into());
+            self
+        }
+
+        pub fn note(mut self, note: impl Into<String>) -> Self {
+            self.note = Some(note.into());
+            self
         }
 
+        pub fn charset(mut self, charset: Charset) -> Self {
+            self.charset = charset;
+            self
+        }
 
-        let res = super::request(req_data, url);
-        let status = res.status();
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if !json["success"].as_bool().unwrap() {
-            return Err(json["message"].to_string());
+        /// checks the spec without sending anything, create_with and create_bulk call this too
+        pub fn validate(&self) -> Result<(), String> {
+            if self.expiry.as_secs() == 0 {
+                return Err("expiry has to be at least one second".to_string());
+            }
+            if self.amount == 0 {
+                return Err("amount has to be at least 1".to_string());
+            }
+            if self.level == 0 {
+                return Err("level has to be at least 1".to_string());
+            }
+            validate_mask(&self.mask)
         }
-        if status == StatusCode::OK {
-            return Ok(vec![json["key"].as_str().unwrap().to_string()]);
-        } else if status == StatusCode::FOUND {
-            return Ok(json["keys"].as_array().unwrap().iter().map(|x| x.as_str().unwrap().to_string()).collect());
+
+        /// keyauth takes the expiry in days
+        pub(crate) fn expiry_days(&self) -> String {
+            let secs = self.expiry.as_secs();
+            if secs / 86400 * 86400 == secs {
+                return (secs / 86400).to_string();
+            }
+            let days = format!("{:.6}", secs as f64 / 86400.0);
+            days.trim_end_matches('0').to_string()
         }
-        Err("SOMETHING RLY BAD HAPPENED PLEASE CONTACT THE LIB DEVELOPER".to_string())
     }
 
-    /// returns message from keyauth Ok(message) if success = true and Err(message) if success = false
-    pub fn verify_license_exists(sellerkey: &str, url: String, license: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("key", license);
-        req_data.insert("type", "verify");
+    /// a mask needs at least one random character and may only contain alphanumerics, `-` and `*`
+    pub fn validate_mask(mask: &str) -> Result<(), String> {
+        if mask.is_empty() {
+            return Err("mask is empty".to_string());
+        }
+        if let Some(c) = mask.chars().find(|c| !c.is_ascii_alphanumeric() && *c != '-' && *c != '*') {
+            return Err(format!("mask contains invalid character {:?}", c));
+        }
+        if !mask.contains(['*', 'X', 'x']) {
+            return Err("mask has no random characters, use * or X".to_string());
+        }
+        Ok(())
+    }
+
+    /// a batch of create_bulk that failed
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct BatchFailure {
+        /// how many keys this batch should have created
+        pub amount: u32,
+        pub error: String,
+    }
+
+    /// result of create_bulk, keys has every key that was created even if some batches failed
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct BulkLicenses {
+        pub keys: Vec<String>,
+        pub failures: Vec<BatchFailure>,
+    }
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
+    impl BulkLicenses {
+        /// number of keys that werent created
+        pub fn failed(&self) -> u32 {
+            self.failures.iter().map(|f| f.amount).sum()
         }
-        Err(json["message"].to_string())
+
+        pub fn is_complete(&self) -> bool {
+            self.failures.is_empty()
+        }
+    }
+
+    /// returns a list of licenses, if errors returns a response message
+    pub fn create(sellerkey: &str, url: String, expiry: u64, mask: Option<String>, level: Option<i32>, amount: Option<u8>, owner: Option<String>) -> Result<Vec<String>, String> {
+        super::SellerClient::new(sellerkey, url).licenses().create(expiry, mask, level, amount, owner)
+    }
+
+    /// creates spec.amount keys in one request, Err if the spec is invalid or amount is over [`MAX_PER_REQUEST`]
+    pub fn create_with(sellerkey: &str, url: String, spec: &LicenseSpec) -> Result<Vec<String>, String> {
+        super::SellerClient::new(sellerkey, url).licenses().create_with(spec)
+    }
+
+    /// creates any amount of keys in batches of [`MAX_PER_REQUEST`], see [`LicensesApi::create_bulk`]
+    pub fn create_bulk(sellerkey: &str, url: String, spec: &LicenseSpec) -> Result<BulkLicenses, String> {
+        super::SellerClient::new(sellerkey, url).licenses().create_bulk(spec)
     }
 
     /// returns message from keyauth Ok(message) if success = true and Err(message) if success = false
-    pub fn use_license_create_user(sellerkey: &str, url: String, user: &str, license: &str, pass: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("key", license);
-        req_data.insert("type", "activate");
-        req_data.insert("user", user);
-        req_data.insert("pass", pass);
+    pub fn verify_license_exists(sellerkey: &str, url: String, license: &str) -> Result<String, String> {
+        super::SellerClient::new(sellerkey, url).licenses().verify_license_exists(license)
+    }
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+    /// returns message from keyauth Ok(message) if success = true and Err(message) if success = false
+    pub fn use_license_create_user(sellerkey: &str, url: String, user: &str, license: &str, pass: &str) -> Result<String, String> {
+        super::SellerClient::new(sellerkey, url).licenses().use_license_create_user(user, license, pass)
     }
 
     /// user_too = 1 deletes the user too, None or 0 for no
     pub fn delete(sellerkey: &str, url: String, license: &str, user_too: Option<bool>) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("key", license);
-        req_data.insert("type", "del");
-        let user_too = match user_too {
-            Some(u) => u,
-            None => false,
-        };
-        let user_too = if user_too { 1 } else { 0 };
-        let user_too = user_too.to_string();
-        req_data.insert("user_too", user_too.as_str());
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).licenses().delete(license, user_too)
     }
 
     pub fn delete_unused(sellerkey: &str, url: String) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "delunused");
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).licenses().delete_unused()
     }
 
     pub fn delete_used(sellerkey: &str, url: String) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "delused");
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).licenses().delete_used()
     }
 
     pub fn delete_all(sellerkey: &str, url: String) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "delalllicenses");
+        super::SellerClient::new(sellerkey, url).licenses().delete_all()
+    }
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+    /// status of a license as shown in the dashboard
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    pub enum LicenseStatus {
+        #[serde(rename = "Not Used")]
+        NotUsed,
+        Used,
+        Banned,
+        Paused,
     }
 
-    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
     pub struct License {
         pub id: String,
         pub key: String,
+        #[serde(default, deserialize_with = "crate::fields::optional_string")]
         pub note: Option<String>,
-        pub expires: String,
-        pub status: String,
-        pub level: String,
-        pub gengy: String,
-        pub gendate: String,
-        pub usedon: Option<String>,
+        /// how long the license lasts once it is used
+        #[serde(deserialize_with = "crate::fields::seconds", serialize_with = "crate::fields::serialize_seconds")]
+        pub expires: Duration,
+        pub status: LicenseStatus,
+        #[serde(deserialize_with = "crate::fields::number")]
+        pub level: u32,
+        pub genby: String,
+        #[serde(deserialize_with = "crate::fields::unix_timestamp", serialize_with = "crate::fields::serialize_unix_timestamp")]
+        pub gendate: SystemTime,
+        #[serde(default, deserialize_with = "crate::fields::optional_unix_timestamp", serialize_with = "crate::fields::serialize_optional_unix_timestamp")]
+        pub usedon: Option<SystemTime>,
+        #[serde(default, deserialize_with = "crate::fields::optional_string")]
         pub usedby: Option<String>,
         pub app: String,
-        pub banned: String,
+        /// ban reason if the license is banned
+        #[serde(default, deserialize_with = "crate::fields::optional_string")]
+        pub banned: Option<String>,
+    }
+
+    impl License {
+        /// when the license runs out, None if it wasnt used yet
+        pub fn expires_at(&self) -> Option<SystemTime> {
+            self.usedon.map(|used| used + self.expires)
+        }
+
+        /// used and past its expiry at time now
+        pub fn is_expired_at(&self, now: SystemTime) -> bool {
+            self.expires_at().is_some_and(|expiry| expiry <= now)
+        }
+
+        pub fn is_expired(&self) -> bool {
+            self.is_expired_at(SystemTime::now())
+        }
+    }
+
+    /// filters for the result of fetch_all
+    pub trait LicenseFilter {
+        /// licenses nobody redeemed yet
+        fn unused(&self) -> Vec<&License>;
+        /// licenses redeemed by user
+        fn used_by(&self, user: &str) -> Vec<&License>;
+        /// used licenses that ran out
+        fn expired(&self) -> Vec<&License>;
+        fn with_status(&self, status: LicenseStatus) -> Vec<&License>;
+    }
+
+    impl LicenseFilter for [License] {
+        fn unused(&self) -> Vec<&License> {
+            self.with_status(LicenseStatus::NotUsed)
+        }
+
+        fn used_by(&self, user: &str) -> Vec<&License> {
+            self.iter().filter(|l| l.usedby.as_deref() == Some(user)).collect()
+        }
+
+        fn expired(&self) -> Vec<&License> {
+            let now = SystemTime::now();
+            self.iter().filter(|l| l.is_expired_at(now)).collect()
+        }
+
+        fn with_status(&self, status: LicenseStatus) -> Vec<&License> {
+            self.iter().filter(|l| l.status == status).collect()
+        }
     }
 
     /// if success = true returns a vector of all keys, the json/Value format can be found here https://docs.keyauth.cc/seller/licenses in the example response
     pub fn fetch_all(sellerkey: &str, url: String) -> Result<Vec<License>, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "fetchallkeys");
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            if json["keys"].is_string() {
-                return Ok(Vec::new());
-            }
-            return Ok(json["keys"].as_array().unwrap().iter().map(|x| serde_json::from_value(x.clone()).unwrap()).collect());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).licenses().fetch_all()
+    }
+
+    pub fn stream_all(sellerkey: &str, url: String) -> super::stream::JsonStream<License> {
+        super::SellerClient::new(sellerkey, url).licenses().stream_all()
     }
 
     /// time is in number of days according to api docs
     pub fn add_time_to_unused(sellerkey: &str, url: String, time: u64) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "addtime");
-        let time = time.to_string();
-        req_data.insert("time", time.as_str());
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).licenses().add_time_to_unused(time)
     }
 
     pub fn ban(sellerkey: &str, url: String, license: &str, reason: &str, user_too: Option<bool>) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "ban");
-        req_data.insert("key", license);
-        req_data.insert("reason", reason);
-        let user_too = match user_too {
-            Some(u) => u,
-            None => false,
-        };
-        let user_too = if user_too { 1 } else { 0 };
-        let user_too = user_too.to_string();
-        req_data.insert("user_too", user_too.as_str());
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).licenses().ban(license, reason, user_too)
     }
 
     pub fn unban(sellerkey: &str, url: String, license: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "unban");
-        req_data.insert("key", license);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).licenses().unban(license)
     }
 
     pub fn retrieve_from_user(sellerkey: &str, url: String, user: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "getkey");
-        req_data.insert("user", user);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["key"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).licenses().retrieve_from_user(user)
     }
 
     pub fn set_note(sellerkey: &str, url: String, license: &str, note: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "setnote");
-        req_data.insert("key", license);
-        req_data.insert("note", note);
+        super::SellerClient::new(sellerkey, url).licenses().set_note(license, note)
+    }
+
+    /// licenses endpoints of a [`SellerClient`](super::SellerClient), returned by [`SellerClient::licenses`](super::SellerClient::licenses)
+    pub struct LicensesApi<'a> {
+        pub(super) client: &'a super::SellerClient,
+    }
+
+    impl LicensesApi<'_> {
+        /// returns a list of licenses, if errors returns a response message
+        pub fn create(&self, expiry: u64, mask: Option<String>, level: Option<i32>, amount: Option<u8>, owner: Option<String>) -> Result<Vec<String>, String> {
+            let level = level.unwrap_or(1);
+            let level = u32::try_from(level).map_err(|_| format!("invalid level {}", level))?;
+            let expiry = expiry.checked_mul(86400).ok_or_else(|| format!("expiry of {} days is too long", expiry))?;
+            let mut spec = LicenseSpec::new(Duration::from_secs(expiry))
+                .level(level)
+                .amount(amount.unwrap_or(1) as u32);
+            if let Some(mask) = mask {
+                spec.mask = mask;
+            }
+            spec.owner = owner.filter(|o| o != "none");
+            // kept unvalidated so calls that worked before still do
+            self.add(&spec, spec.amount)
+        }
+
+        /// creates spec.amount keys in one request, Err if the spec is invalid or amount is over [`MAX_PER_REQUEST`]
+        pub fn create_with(&self, spec: &LicenseSpec) -> Result<Vec<String>, String> {
+            spec.validate()?;
+            if spec.amount > MAX_PER_REQUEST {
+                return Err(format!("amount is over {}, use create_bulk", MAX_PER_REQUEST));
+            }
+            self.add(spec, spec.amount)
+        }
+
+        /// creates any amount of keys in batches of [`MAX_PER_REQUEST`].
+        /// a failed batch doesnt stop the others, Err only if the spec is invalid or every batch failed
+        pub fn create_bulk(&self, spec: &LicenseSpec) -> Result<BulkLicenses, String> {
+            spec.validate()?;
+            let mut result = BulkLicenses::default();
+            let mut remaining = spec.amount;
+            while remaining > 0 {
+                let amount = remaining.min(MAX_PER_REQUEST);
+                match self.add(spec, amount) {
+                    Ok(keys) => result.keys.extend(keys),
+                    Err(error) => result.failures.push(BatchFailure { amount, error }),
+                }
+                remaining -= amount;
+            }
+            if result.keys.is_empty() {
+                if let Some(failure) = result.failures.pop() {
+                    return Err(failure.error);
+                }
+            }
+            Ok(result)
+        }
+
+        pub(super) fn add(&self, spec: &LicenseSpec, amount: u32) -> Result<Vec<String>, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("type", "add");
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("format", "JSON");
+            let expiry = spec.expiry_days();
+            req_data.insert("expiry", expiry.as_str());
+            req_data.insert("mask", spec.mask.as_str());
+            let level = spec.level.to_string();
+            req_data.insert("level", level.as_str());
+            let amount = amount.to_string();
+            req_data.insert("amount", amount.as_str());
+            req_data.insert("character", spec.charset.as_param());
+            if let Some(owner) = &spec.owner {
+                req_data.insert("owner", owner.as_str());
+            }
+            if let Some(note) = &spec.note {
+                req_data.insert("note", note.as_str());
+            }
+
+            let json = self.client.json(req_data)?;
+            // one key comes back as "key", more as "keys"
+            if let Some(keys) = json["keys"].as_array() {
+                return Ok(keys.iter().filter_map(|k| k.as_str()).map(str::to_string).collect());
+            }
+            match json["key"].as_str() {
+                Some(key) => Ok(vec![key.to_string()]),
+                None => Err("response contains no keys".to_string()),
+            }
+        }
+
+        /// returns message from keyauth Ok(message) if success = true and Err(message) if success = false
+        pub fn verify_license_exists(&self, license: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("key", license);
+            req_data.insert("type", "verify");
+
+            self.client.message(req_data)
+        }
+
+        /// returns message from keyauth Ok(message) if success = true and Err(message) if success = false
+        pub fn use_license_create_user(&self, user: &str, license: &str, pass: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("key", license);
+            req_data.insert("type", "activate");
+            req_data.insert("user", user);
+            req_data.insert("pass", pass);
+
+            self.client.message(req_data)
+        }
+
+        /// user_too = 1 deletes the user too, None or 0 for no
+        pub fn delete(&self, license: &str, user_too: Option<bool>) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("key", license);
+            req_data.insert("type", "del");
+            let user_too = match user_too {
+               Some(u) => u,
+               None => false,
+            };
+            let user_too = if user_too { 1 } else { 0 };
+            let user_too = user_too.to_string();
+            req_data.insert("user_too", user_too.as_str());
+
+            self.client.message(req_data)
+        }
+
+        pub fn delete_unused(&self) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "delunused");
+
+            self.client.message(req_data)
+        }
+
+        pub fn delete_used(&self) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "delused");
+
+            self.client.message(req_data)
+        }
+
+        pub fn delete_all(&self) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "delalllicenses");
+
+            self.client.message(req_data)
+        }
+
+        /// if success = true returns a vector of all keys, the json/Value format can be found here https://docs.keyauth.cc/seller/licenses in the example response
+        pub fn fetch_all(&self) -> Result<Vec<License>, String> {
+            self.client.fetch_list("fetchallkeys", "keys")
+        }
+
+        /// same as [`fetch_all`](Self::fetch_all) but parses the response while it downloads, one item at a time
+        pub fn stream_all(&self) -> super::stream::JsonStream<License> {
+            self.client.stream("fetchallkeys", "keys")
+        }
+
+        /// time is in number of days according to api docs
+        pub fn add_time_to_unused(&self, time: u64) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "addtime");
+            let time = time.to_string();
+            req_data.insert("time", time.as_str());
+
+            self.client.message(req_data)
+        }
+
+        pub fn ban(&self, license: &str, reason: &str, user_too: Option<bool>) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "ban");
+            req_data.insert("key", license);
+            req_data.insert("reason", reason);
+            let user_too = match user_too {
+               Some(u) => u,
+               None => false,
+            };
+            let user_too = if user_too { 1 } else { 0 };
+            let user_too = user_too.to_string();
+            req_data.insert("user_too", user_too.as_str());
+
+            self.client.message(req_data)
+        }
+
+        pub fn unban(&self, license: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "unban");
+            req_data.insert("key", license);
+
+            self.client.message(req_data)
+        }
+
+        pub fn retrieve_from_user(&self, user: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "getkey");
+            req_data.insert("user", user);
+
+            let json = self.client.json(req_data)?;
+            Ok(super::text(&json["key"]))
+        }
+
+        pub fn set_note(&self, license: &str, note: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "setnote");
+            req_data.insert("key", license);
+            req_data.insert("note", note);
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
+            self.client.message(req_data)
         }
-        Err(json["message"].to_string())
     }
 }
 /// https://docs.keyauth.cc/seller/users
 pub mod user {
     use std::collections::HashMap;
+    use std::time::SystemTime;
     use serde_json::Value;
 
     /// if pass -> Null then the password will be set when the user first logs in
     pub fn create(sellerkey: &str, url: String, name: &str, sub: &str, expiry: u64, pass: Option<String>) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "adduser");
-        req_data.insert("user", name);
-        req_data.insert("sub", sub);
-        let expiry = expiry.to_string();
-        req_data.insert("expiry", expiry.as_str());
-        let pass = match pass {
-            Some(p) => p,
-            None => "null".to_string(),
-        };
-        if pass != "null" {
-            req_data.insert("pass", pass.as_str());
-        }
-
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().create(name, sub, expiry, pass)
     }
 
     pub fn delete(sellerkey: &str, url: String, name: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "deluser");
-        req_data.insert("user", name);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().delete(name)
     }
 
     pub fn delete_expired(sellerkey: &str, url: String) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "delexpusers");
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().delete_expired()
     }
 
     pub fn reset_hwid(sellerkey: &str, url: String, name: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "resetuser");
-        req_data.insert("user", name);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().reset_hwid(name)
     }
 
     /// name can be all or a specific user
     pub fn set_var(sellerkey: &str, url: String, name: &str, var: &str, value: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "setvar");
-        req_data.insert("user", name);
-        req_data.insert("var", var);
-        req_data.insert("data", value);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().set_var(name, var, value)
     }
 
     pub fn get_var_data(sellerkey: &str, url: String, name: &str, var: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "getvar");
-        req_data.insert("user", name);
-        req_data.insert("var", var);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["response"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().get_var_data(name, var)
     }
 
     pub fn del_all_vars(sellerkey: &str, url: String, var: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "massUserVarDelete");
-        req_data.insert("name", var);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().del_all_vars(var)
     }
 
     pub fn ban(sellerkey: &str, url: String, name: &str, reason: Option<String>) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "banuser");
-        req_data.insert("user", name);
-        let reason = match reason {
-            Some(r) => r,
-            None => "null".to_string(),
-        };
-        if reason != "null" {
-            req_data.insert("reason", &reason);
-        }
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().ban(name, reason)
     }
 
     pub fn unban(sellerkey: &str, url: String, name: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "unbanuser");
-        req_data.insert("user", name);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().unban(name)
     }
 
     pub fn delete_var(sellerkey: &str, url: String, name: &str, var: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "deluservar");
-        req_data.insert("user", name);
-        req_data.insert("var", var);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().delete_var(name, var)
     }
 
     pub fn delete_user_subscription(sellerkey: &str, url: String, name: &str, sub: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "delsub");
-        req_data.insert("user", name);
-        req_data.insert("sub", sub);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().delete_user_subscription(name, sub)
     }
 
     /// name can be all
     pub fn extend_user_subscription(sellerkey: &str, url: String, name: &str, sub: &str, days: &str, active_only: Option<bool>) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "extend");
-        req_data.insert("user", name);
-        req_data.insert("sub", sub);
-        req_data.insert("expiry", days);
-        let active_only = match active_only {
-            Some(a) => a,
-            None => false,
-        };
-        let active_only = if active_only { "1" } else { "0" };
-        req_data.insert("active_only", active_only);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().extend_user_subscription(name, sub, days, active_only)
     }
 
     /// name can be all
     pub fn subtract_subscription(sellerkey: &str, url: String, name: &str, sub: &str, seconds: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "subtract");
-        req_data.insert("user", name);
-        req_data.insert("sub", sub);
-        req_data.insert("seconds", seconds);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().subtract_subscription(name, sub, seconds)
     }
 
     pub fn delete_all_user_subscriptions(sellerkey: &str, url: String) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "dellallusers");
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().delete_all_user_subscriptions()
     }
 
     pub fn reset_all_hwid(sellerkey: &str, url: String) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "resetalluser");
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().reset_all_hwid()
     }
 
     pub fn verify_exists(sellerkey: &str, url: String, name: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "verifyuser");
-        req_data.insert("user", name);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().verify_exists(name)
     }
 
     pub fn add_hwid(sellerkey: &str, url: String, name: &str, hwid: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "addhwiduser");
-        req_data.insert("user", name);
-        req_data.insert("hwid", hwid);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().add_hwid(name, hwid)
     }
 
-    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
     pub struct User {
         pub id: String,
         pub username: String,
+        #[serde(default, deserialize_with = "crate::fields::optional_string")]
         pub email: Option<String>,
+        /// password hash
+        #[serde(default, deserialize_with = "crate::fields::optional_string")]
         pub password: Option<String>,
+        #[serde(default, deserialize_with = "crate::fields::optional_string")]
         pub hwid: Option<String>,
         pub app: String,
         pub owner: String,
-        pub createdate: String,
-        pub lastlogin: Option<String>,
+        #[serde(deserialize_with = "crate::fields::unix_timestamp", serialize_with = "crate::fields::serialize_unix_timestamp")]
+        pub createdate: SystemTime,
+        /// None if the user never logged in
+        #[serde(default, deserialize_with = "crate::fields::optional_unix_timestamp", serialize_with = "crate::fields::serialize_optional_unix_timestamp")]
+        pub lastlogin: Option<SystemTime>,
+        /// ban reason if the user is banned
+        #[serde(default, deserialize_with = "crate::fields::optional_string")]
         pub banned: Option<String>,
+        #[serde(default, deserialize_with = "crate::fields::optional_string")]
         pub ip: Option<String>,
-        pub cooldown: Option<String>,
+        /// when the hwid reset cooldown ends
+        #[serde(default, deserialize_with = "crate::fields::optional_unix_timestamp", serialize_with = "crate::fields::serialize_optional_unix_timestamp")]
+        pub cooldown: Option<SystemTime>,
     }
 
     pub fn fetch_all_users(sellerkey: &str, url: String) -> Result<Vec<User>, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "fetchallusers");
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            if json["users"].is_string() {
-                return Ok(Vec::new());
-            }
-            return Ok(json["users"].as_array().unwrap().iter().map(|x| serde_json::from_value(x.clone()).unwrap()).collect());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().fetch_all_users()
     }
 
-    pub fn change_password(sellerkey: &str, url: String, name: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "resetpw");
-        req_data.insert("user", name);
+    pub fn stream_all_users(sellerkey: &str, url: String) -> super::stream::JsonStream<User> {
+        super::SellerClient::new(sellerkey, url).users().stream_all_users()
+    }
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+    pub fn change_password(sellerkey: &str, url: String, name: &str) -> Result<String, String> {
+        super::SellerClient::new(sellerkey, url).users().change_password(name)
     }
 
-    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
     pub struct UserVar {
         pub name: String,
         pub data: String,
         pub user: String,
+        /// the user cant change it from the client
+        #[serde(rename = "readOnly", default, deserialize_with = "crate::fields::flag")]
+        pub read_only: bool,
     }
 
     pub fn fetch_all_vars(sellerkey: &str, url: String) -> Result<Vec<UserVar>, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "fetchalluservars");
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            if json["uservars"].is_string() {
-                return Ok(Vec::new());
-            }
-            return Ok(json["vars"].as_array().unwrap().iter().map(|x| serde_json::from_value(x.clone()).unwrap()).collect());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().fetch_all_vars()
+    }
+
+    pub fn stream_all_vars(sellerkey: &str, url: String) -> super::stream::JsonStream<UserVar> {
+        super::SellerClient::new(sellerkey, url).users().stream_all_vars()
     }
 
-    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
     pub struct UserData {
         pub username: String,
         pub subscriptions: Vec<UserSubscription>,
+        #[serde(default, deserialize_with = "crate::fields::optional_string")]
         pub ip: Option<String>,
+        #[serde(default, deserialize_with = "crate::fields::optional_string")]
         pub hwid: Option<String>,
-        pub createdate: String,
-        pub lastlogin: Option<String>,
-        pub cooldown: Option<String>,
+        #[serde(deserialize_with = "crate::fields::unix_timestamp", serialize_with = "crate::fields::serialize_unix_timestamp")]
+        pub createdate: SystemTime,
+        #[serde(default, deserialize_with = "crate::fields::optional_unix_timestamp", serialize_with = "crate::fields::serialize_optional_unix_timestamp")]
+        pub lastlogin: Option<SystemTime>,
+        #[serde(default, deserialize_with = "crate::fields::optional_unix_timestamp", serialize_with = "crate::fields::serialize_optional_unix_timestamp")]
+        pub cooldown: Option<SystemTime>,
         pub token: String,
     }
 
-    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
     pub struct UserSubscription {
         pub subscription: String,
-        pub expiry: String,
+        #[serde(deserialize_with = "crate::fields::unix_timestamp", serialize_with = "crate::fields::serialize_unix_timestamp")]
+        pub expiry: SystemTime,
+        /// license the subscription came from
+        #[serde(default, deserialize_with = "crate::fields::optional_string")]
         pub key: Option<String>,
     }
 
-    pub fn user_data(sellerkey: &str, url: String, name: &str) -> Result<UserData, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "userdata");
-        req_data.insert("user", name);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(serde_json::from_value(json).unwrap());
+    impl UserSubscription {
+        pub fn is_active_at(&self, now: SystemTime) -> bool {
+            self.expiry > now
         }
-        Err(json["message"].to_string())
+    }
+
+    pub fn user_data(sellerkey: &str, url: String, name: &str) -> Result<UserData, String> {
+        super::SellerClient::new(sellerkey, url).users().user_data(name)
     }
 
     pub fn fetch_all_usernames(sellerkey: &str, url: String) -> Result<Vec<String>, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "fetchallusernames");
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            if json["usernames"].is_string() {
-                return Ok(Vec::new());
-            }
-            return Ok(json["usernames"].as_array().unwrap().iter().map(|x| x["username"].to_string()).collect());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().fetch_all_usernames()
     }
 
     pub fn count_subscriptions(sellerkey: &str, url: String, name: &str) -> Result<i64, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "countsubs");
-        req_data.insert("name", name);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["count"].to_string().parse().unwrap());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).users().count_subscriptions(name)
     }
 
     /// cooldown in seconds
     pub fn set_user_cooldown(sellerkey: &str, url: String, name: &str, cooldown: i64) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "setcooldown");
-        req_data.insert("user", name);
-        let cooldown = cooldown.to_string();
-        req_data.insert("cooldown", cooldown.as_str());
+        super::SellerClient::new(sellerkey, url).users().set_user_cooldown(name, cooldown)
+    }
+
+    /// user endpoints of a [`SellerClient`](super::SellerClient), returned by [`SellerClient::users`](super::SellerClient::users)
+    pub struct UserApi<'a> {
+        pub(super) client: &'a super::SellerClient,
+    }
+
+    impl UserApi<'_> {
+        /// if pass -> Null then the password will be set when the user first logs in
+        pub fn create(&self, name: &str, sub: &str, expiry: u64, pass: Option<String>) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "adduser");
+            req_data.insert("user", name);
+            req_data.insert("sub", sub);
+            let expiry = expiry.to_string();
+            req_data.insert("expiry", expiry.as_str());
+            let pass = match pass {
+               Some(p) => p,
+               None => "null".to_string(),
+            };
+            if pass != "null" {
+               req_data.insert("pass", pass.as_str());
+            }
+
+
+            self.client.message(req_data)
+        }
+
+        pub fn delete(&self, name: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "deluser");
+            req_data.insert("user", name);
+
+            self.client.message(req_data)
+        }
+
+        pub fn delete_expired(&self) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "delexpusers");
+
+            self.client.message(req_data)
+        }
+
+        pub fn reset_hwid(&self, name: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "resetuser");
+            req_data.insert("user", name);
+
+            self.client.message(req_data)
+        }
+
+        /// name can be all or a specific user
+        pub fn set_var(&self, name: &str, var: &str, value: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "setvar");
+            req_data.insert("user", name);
+            req_data.insert("var", var);
+            req_data.insert("data", value);
+
+            self.client.message(req_data)
+        }
+
+        pub fn get_var_data(&self, name: &str, var: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "getvar");
+            req_data.insert("user", name);
+            req_data.insert("var", var);
+
+            let json = self.client.json(req_data)?;
+            Ok(super::text(&json["response"]))
+        }
+
+        pub fn del_all_vars(&self, var: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "massUserVarDelete");
+            req_data.insert("name", var);
+
+            self.client.message(req_data)
+        }
+
+        pub fn ban(&self, name: &str, reason: Option<String>) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "banuser");
+            req_data.insert("user", name);
+            let reason = match reason {
+               Some(r) => r,
+               None => "null".to_string(),
+            };
+            if reason != "null" {
+               req_data.insert("reason", &reason);
+            }
+
+            self.client.message(req_data)
+        }
+
+        pub fn unban(&self, name: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "unbanuser");
+            req_data.insert("user", name);
+
+            self.client.message(req_data)
+        }
+
+        pub fn delete_var(&self, name: &str, var: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "deluservar");
+            req_data.insert("user", name);
+            req_data.insert("var", var);
+
+            self.client.message(req_data)
+        }
+
+        pub fn delete_user_subscription(&self, name: &str, sub: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "delsub");
+            req_data.insert("user", name);
+            req_data.insert("sub", sub);
+
+            self.client.message(req_data)
+        }
+
+        /// name can be all
+        pub fn extend_user_subscription(&self, name: &str, sub: &str, days: &str, active_only: Option<bool>) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "extend");
+            req_data.insert("user", name);
+            req_data.insert("sub", sub);
+            req_data.insert("expiry", days);
+            let active_only = match active_only {
+               Some(a) => a,
+               None => false,
+            };
+            let active_only = if active_only { "1" } else { "0" };
+            req_data.insert("active_only", active_only);
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
+            self.client.message(req_data)
+        }
+
+        /// name can be all
+        pub fn subtract_subscription(&self, name: &str, sub: &str, seconds: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "subtract");
+            req_data.insert("user", name);
+            req_data.insert("sub", sub);
+            req_data.insert("seconds", seconds);
+
+            self.client.message(req_data)
+        }
+
+        pub fn delete_all_user_subscriptions(&self) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "dellallusers");
+
+            self.client.message(req_data)
+        }
+
+        pub fn reset_all_hwid(&self) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "resetalluser");
+
+            self.client.message(req_data)
+        }
+
+        pub fn verify_exists(&self, name: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "verifyuser");
+            req_data.insert("user", name);
+
+            self.client.message(req_data)
+        }
+
+        pub fn add_hwid(&self, name: &str, hwid: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "addhwiduser");
+            req_data.insert("user", name);
+            req_data.insert("hwid", hwid);
+
+            self.client.message(req_data)
+        }
+
+        pub fn fetch_all_users(&self) -> Result<Vec<User>, String> {
+            self.client.fetch_list("fetchallusers", "users")
+        }
+
+        /// same as [`fetch_all_users`](Self::fetch_all_users) but parses the response while it downloads, one item at a time
+        pub fn stream_all_users(&self) -> super::stream::JsonStream<User> {
+            self.client.stream("fetchallusers", "users")
+        }
+
+        pub fn change_password(&self, name: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "resetpw");
+            req_data.insert("user", name);
+
+            self.client.message(req_data)
+        }
+
+        pub fn fetch_all_vars(&self) -> Result<Vec<UserVar>, String> {
+            self.client.fetch_list("fetchalluservars", "vars")
+        }
+
+        /// same as [`fetch_all_vars`](Self::fetch_all_vars) but parses the response while it downloads, one item at a time
+        pub fn stream_all_vars(&self) -> super::stream::JsonStream<UserVar> {
+            self.client.stream("fetchalluservars", "vars")
+        }
+
+        pub fn user_data(&self, name: &str) -> Result<UserData, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "userdata");
+            req_data.insert("user", name);
+
+            let json = self.client.json(req_data)?;
+            serde_json::from_value(json).map_err(|e| e.to_string())
+        }
+
+        pub fn fetch_all_usernames(&self) -> Result<Vec<String>, String> {
+            #[derive(serde::Deserialize)]
+            struct Username {
+                username: String,
+            }
+            let usernames: Vec<Username> = self.client.fetch_list("fetchallusernames", "usernames")?;
+            Ok(usernames.into_iter().map(|x| x.username).collect())
+        }
+
+        pub fn count_subscriptions(&self, name: &str) -> Result<i64, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "countsubs");
+            req_data.insert("name", name);
+
+            let json = self.client.json(req_data)?;
+            let count = match &json["count"] {
+                Value::Number(n) => n.as_i64(),
+                Value::String(s) => s.trim().parse().ok(),
+                _ => None,
+            };
+            count.ok_or_else(|| format!("invalid count in response: {}", json["count"]))
+        }
+
+        /// cooldown in seconds
+        pub fn set_user_cooldown(&self, name: &str, cooldown: i64) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "setcooldown");
+            req_data.insert("user", name);
+            let cooldown = cooldown.to_string();
+            req_data.insert("cooldown", cooldown.as_str());
+
+            self.client.message(req_data)
         }
-        Err(json["message"].to_string())
     }
 }
 
 pub mod subscriptions {
     use std::collections::HashMap;
-    use serde_json::Value;
 
     pub fn create(sellerkey: &str, url: String, name: &str, level: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "addsub");
-        req_data.insert("name", name);
-        req_data.insert("level", level);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).subscriptions().create(name, level)
     }
 
     pub fn delete(sellerkey: &str, url: String, name: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "delappsub");
-        req_data.insert("name", name);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).subscriptions().delete(name)
     }
 
-    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
     pub struct Sub {
         pub name: String,
-        pub level: String,
+        #[serde(deserialize_with = "crate::fields::number")]
+        pub level: u32,
     }
 
     pub fn fetch_all(sellerkey: &str, url: String) -> Result<Vec<Sub>, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "fetchallsubs");
+        super::SellerClient::new(sellerkey, url).subscriptions().fetch_all()
+    }
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["subs"].as_array().unwrap().to_vec().iter().map(|x| serde_json::from_value(x.clone()).unwrap()).collect());
-        }
-        Err(json["message"].to_string())
+    pub fn stream_all(sellerkey: &str, url: String) -> super::stream::JsonStream<Sub> {
+        super::SellerClient::new(sellerkey, url).subscriptions().stream_all()
     }
 
     pub fn edit(sellerkey: &str, url: String, name: &str, level: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "editsub");
-        req_data.insert("name", name);
-        req_data.insert("level", level);
+        super::SellerClient::new(sellerkey, url).subscriptions().edit(name, level)
+    }
+
+    /// subscriptions endpoints of a [`SellerClient`](super::SellerClient), returned by [`SellerClient::subscriptions`](super::SellerClient::subscriptions)
+    pub struct SubscriptionsApi<'a> {
+        pub(super) client: &'a super::SellerClient,
+    }
+
+    impl SubscriptionsApi<'_> {
+        pub fn create(&self, name: &str, level: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "addsub");
+            req_data.insert("name", name);
+            req_data.insert("level", level);
+
+            self.client.message(req_data)
+        }
+
+        pub fn delete(&self, name: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "delappsub");
+            req_data.insert("name", name);
+
+            self.client.message(req_data)
+        }
+
+        pub fn fetch_all(&self) -> Result<Vec<Sub>, String> {
+            self.client.fetch_list("fetchallsubs", "subs")
+        }
+
+        /// same as [`fetch_all`](Self::fetch_all) but parses the response while it downloads, one item at a time
+        pub fn stream_all(&self) -> super::stream::JsonStream<Sub> {
+            self.client.stream("fetchallsubs", "subs")
+        }
+
+        pub fn edit(&self, name: &str, level: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "editsub");
+            req_data.insert("name", name);
+            req_data.insert("level", level);
+
+            self.client.message(req_data)
+        }
+    }
+}
+
+pub mod chat {
+    use std::collections::HashMap;
+    use std::time::SystemTime;
+
+    /// delay in seconds
+    pub fn create_channel(sellerkey: &str, url: String, name: &str, delay: &str) -> Result<String, String> {
+        super::SellerClient::new(sellerkey, url).chat().create_channel(name, delay)
+    }
+
+    pub fn delete_channel(sellerkey: &str, url: String, name: &str) -> Result<String, String> {
+        super::SellerClient::new(sellerkey, url).chat().delete_channel(name)
+    }
+
+    pub fn edit_channel(sellerkey: &str, url: String, name: &str, delay: &str) -> Result<String, String> {
+        super::SellerClient::new(sellerkey, url).chat().edit_channel(name, delay)
+    }
+
+    pub fn clear_channel(sellerkey: &str, url: String, name: &str) -> Result<String, String> {
+        super::SellerClient::new(sellerkey, url).chat().clear_channel(name)
+    }
+
+    /// time in seconds
+    pub fn mute_user(sellerkey: &str, url: String, user: &str, time: u64) -> Result<String, String> {
+        super::SellerClient::new(sellerkey, url).chat().mute_user(user, time)
+    }
+
+    pub fn unmute_user(sellerkey: &str, url: String, user: &str) -> Result<String, String> {
+        super::SellerClient::new(sellerkey, url).chat().unmute_user(user)
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    pub struct Chat {
+        pub name: String,
+        /// seconds between messages
+        #[serde(deserialize_with = "crate::fields::number")]
+        pub delay: u64,
+    }
+
+    pub fn fetch_all_channels(sellerkey: &str, url: String) -> Result<Vec<Chat>, String> {
+        super::SellerClient::new(sellerkey, url).chat().fetch_all_channels()
+    }
+
+    pub fn stream_all_channels(sellerkey: &str, url: String) -> super::stream::JsonStream<Chat> {
+        super::SellerClient::new(sellerkey, url).chat().stream_all_channels()
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    pub struct Mute {
+        pub user: String,
+        /// when the mute ends
+        #[serde(rename = "time", deserialize_with = "crate::fields::unix_timestamp", serialize_with = "crate::fields::serialize_unix_timestamp")]
+        pub until: SystemTime,
+    }
+
+    pub fn fetch_all_mutes(sellerkey: &str, url: String) -> Result<Vec<Mute>, String> {
+        super::SellerClient::new(sellerkey, url).chat().fetch_all_mutes()
+    }
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+    pub fn stream_all_mutes(sellerkey: &str, url: String) -> super::stream::JsonStream<Mute> {
+        super::SellerClient::new(sellerkey, url).chat().stream_all_mutes()
     }
-}
 
-pub mod chat {
-    use std::collections::HashMap;
-    use serde_json::Value;
+    /// chat endpoints of a [`SellerClient`](super::SellerClient), returned by [`SellerClient::chat`](super::SellerClient::chat)
+    pub struct ChatApi<'a> {
+        pub(super) client: &'a super::SellerClient,
+    }
 
-    /// delay in seconds
-    pub fn create_channel(sellerkey: &str, url: String, name: &str, delay: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "addchannel");
-        req_data.insert("name", name);
-        req_data.insert("delay", delay);
+    impl ChatApi<'_> {
+        /// delay in seconds
+        pub fn create_channel(&self, name: &str, delay: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "addchannel");
+            req_data.insert("name", name);
+            req_data.insert("delay", delay);
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
+            self.client.message(req_data)
         }
-        Err(json["message"].to_string())
-    }
 
-    pub fn delete_channel(sellerkey: &str, url: String, name: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "delchannel");
-        req_data.insert("name", name);
+        pub fn delete_channel(&self, name: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "delchannel");
+            req_data.insert("name", name);
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
+            self.client.message(req_data)
         }
-        Err(json["message"].to_string())
-    }
 
-    pub fn edit_channel(sellerkey: &str, url: String, name: &str, delay: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "editchan");
-        req_data.insert("name", name);
-        req_data.insert("delay", delay);
+        pub fn edit_channel(&self, name: &str, delay: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "editchan");
+            req_data.insert("name", name);
+            req_data.insert("delay", delay);
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
+            self.client.message(req_data)
         }
-        Err(json["message"].to_string())
-    }
 
-    pub fn clear_channel(sellerkey: &str, url: String, name: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "clearchannel");
-        req_data.insert("name", name);
+        pub fn clear_channel(&self, name: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "clearchannel");
+            req_data.insert("name", name);
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
+            self.client.message(req_data)
         }
-        Err(json["message"].to_string())
-    }
 
-    /// time in seconds
-    pub fn mute_user(sellerkey: &str, url: String, user: &str, time: u64) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "muteuser");
-        req_data.insert("user", user);
-        let time = time.to_string();
-        req_data.insert("time", time.as_str());
+        /// time in seconds
+        pub fn mute_user(&self, user: &str, time: u64) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "muteuser");
+            req_data.insert("user", user);
+            let time = time.to_string();
+            req_data.insert("time", time.as_str());
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
+            self.client.message(req_data)
         }
-        Err(json["message"].to_string())
-    }
 
-    pub fn unmute_user(sellerkey: &str, url: String, user: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "unmuteuser");
-        req_data.insert("user", user);
+        pub fn unmute_user(&self, user: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "unmuteuser");
+            req_data.insert("user", user);
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
+            self.client.message(req_data)
         }
-        Err(json["message"].to_string())
-    }
 
-    #[derive(serde::Serialize, serde::Deserialize)]
-    pub struct Chat {
-        pub name: String,
-        pub delay: u64,
-    }
+        pub fn fetch_all_channels(&self) -> Result<Vec<Chat>, String> {
+            self.client.fetch_list("fetchallchats", "chats")
+        }
 
-    pub fn fetch_all_channels(sellerkey: &str, url: String) -> Result<Vec<Chat>, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "fetchallchats");
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            if json["chats"].is_string() {
-                return Ok(Vec::new());
-            }
-            return Ok(json["chats"].as_array().unwrap().iter().map(|x| serde_json::from_value(x.clone()).unwrap()).collect());
+        /// same as [`fetch_all_channels`](Self::fetch_all_channels) but parses the response while it downloads, one item at a time
+        pub fn stream_all_channels(&self) -> super::stream::JsonStream<Chat> {
+            self.client.stream("fetchallchats", "chats")
         }
-        Err(json["message"].to_string())
-    }
 
-    pub fn fetch_all_mutes(sellerkey: &str, url: String) -> Result<Vec<Value>, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "fetchallmutes");
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            if json["mutes"].is_string() {
-                return Ok(Vec::new());
-            }
-            return Ok(json["mutes"].as_array().unwrap().to_vec());
+        pub fn fetch_all_mutes(&self) -> Result<Vec<Mute>, String> {
+            self.client.fetch_list("fetchallmutes", "mutes")
+        }
+
+        /// same as [`fetch_all_mutes`](Self::fetch_all_mutes) but parses the response while it downloads, one item at a time
+        pub fn stream_all_mutes(&self) -> super::stream::JsonStream<Mute> {
+            self.client.stream("fetchallmutes", "mutes")
         }
-        Err(json["message"].to_string())
     }
 }
 
 pub mod sessions {
     use std::collections::HashMap;
-    use serde_json::Value;
+    use std::time::SystemTime;
 
     pub fn kill(sellerkey: &str, url: String, session: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "kill");
-        req_data.insert("session", session);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).sessions().kill(session)
     }
 
     pub fn kill_all(sellerkey: &str, url: String) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "killall");
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).sessions().kill_all()
     }
 
-    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
     pub struct Session {
         pub id: String,
+        /// username or license the session logged in with, None before login
+        #[serde(default, deserialize_with = "crate::fields::optional_string")]
         pub credential: Option<String>,
-        pub expiry: String,
-        pub validated: String,
-        pub ip: String,
+        #[serde(deserialize_with = "crate::fields::unix_timestamp", serialize_with = "crate::fields::serialize_unix_timestamp")]
+        pub expiry: SystemTime,
+        /// logged in
+        #[serde(default, deserialize_with = "crate::fields::flag")]
+        pub validated: bool,
+        #[serde(default, deserialize_with = "crate::fields::optional_string")]
+        pub ip: Option<String>,
     }
 
-    pub fn list_all(sellerkey: &str, url: String) -> Result<Vec<Value>, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "fetchallsessions");
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            if json["sessions"].is_string() {
-                return Ok(Vec::new());
-            }
-            return Ok(json["sessions"].as_array().unwrap().to_vec());
+    pub fn list_all(sellerkey: &str, url: String) -> Result<Vec<Session>, String> {
+        super::SellerClient::new(sellerkey, url).sessions().list_all()
+    }
+
+    pub fn stream_all(sellerkey: &str, url: String) -> super::stream::JsonStream<Session> {
+        super::SellerClient::new(sellerkey, url).sessions().stream_all()
+    }
+
+    /// sessions endpoints of a [`SellerClient`](super::SellerClient), returned by [`SellerClient::sessions`](super::SellerClient::sessions)
+    pub struct SessionsApi<'a> {
+        pub(super) client: &'a super::SellerClient,
+    }
+
+    impl SessionsApi<'_> {
+        pub fn kill(&self, session: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "kill");
+            req_data.insert("session", session);
+
+            self.client.message(req_data)
+        }
+
+        pub fn kill_all(&self) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "killall");
+
+            self.client.message(req_data)
+        }
+
+        pub fn list_all(&self) -> Result<Vec<Session>, String> {
+            self.client.fetch_list("fetchallsessions", "sessions")
+        }
+
+        /// same as [`list_all`](Self::list_all) but parses the response while it downloads, one item at a time
+        pub fn stream_all(&self) -> super::stream::JsonStream<Session> {
+            self.client.stream("fetchallsessions", "sessions")
         }
-        Err(json["message"].to_string())
     }
 }
 
 pub fn webhook_create(sellerkey: &str, url: String, baseurl: &str, user_agent: &str, authed: Option<bool>) -> Result<String, String> {
-    let mut req_data = HashMap::new();
-    req_data.insert("sellerkey", sellerkey);
-    req_data.insert("type", "addwebhook");
-    req_data.insert("baseurl", baseurl);
-    req_data.insert("ua", user_agent);
-    let authed = authed.unwrap_or(false);
-    let authed = if authed { "1" } else { "0" };
-    req_data.insert("authed", authed);
-
-    let res = request(req_data, url);
-    let resp = res.text().unwrap();
-    let json: Value = serde_json::from_str(&resp).unwrap();
-    if json["success"].as_bool().unwrap() {
-        return Ok(json["message"].to_string());
-    }
-    Err(json["message"].to_string())
+    SellerClient::new(sellerkey, url).webhook_create(baseurl, user_agent, authed)
 }
 
 pub mod files {
     use std::collections::HashMap;
-    use serde_json::Value;
 
     pub fn upload(sellerkey: &str, url: String, url_to_file: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "upload");
-        req_data.insert("url", url_to_file);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).files().upload(url_to_file)
     }
 
     pub fn delete(sellerkey: &str, url: String, file_id: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "delfile");
-        req_data.insert("fileid", file_id);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).files().delete(file_id)
     }
 
     pub fn del_all_files(sellerkey: &str, url: String) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "delallfiles");
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).files().del_all_files()
     }
 
-    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
     pub struct File {
         pub id: String,
         pub url: String,
     }
 
     pub fn fetch_all_files(sellerkey: &str, url: String) -> Result<Vec<File>, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "fetchallfiles");
+        super::SellerClient::new(sellerkey, url).files().fetch_all_files()
+    }
+
+    pub fn stream_all_files(sellerkey: &str, url: String) -> super::stream::JsonStream<File> {
+        super::SellerClient::new(sellerkey, url).files().stream_all_files()
+    }
+
+    /// files endpoints of a [`SellerClient`](super::SellerClient), returned by [`SellerClient::files`](super::SellerClient::files)
+    pub struct FilesApi<'a> {
+        pub(super) client: &'a super::SellerClient,
+    }
+
+    impl FilesApi<'_> {
+        pub fn upload(&self, url_to_file: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "upload");
+            req_data.insert("url", url_to_file);
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["files"].as_array().unwrap().iter().map(|x| serde_json::from_value(x.clone()).unwrap()).collect());
+            self.client.message(req_data)
+        }
+
+        pub fn delete(&self, file_id: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "delfile");
+            req_data.insert("fileid", file_id);
+
+            self.client.message(req_data)
+        }
+
+        pub fn del_all_files(&self) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "delallfiles");
+
+            self.client.message(req_data)
+        }
+
+        pub fn fetch_all_files(&self) -> Result<Vec<File>, String> {
+            self.client.fetch_list("fetchallfiles", "files")
+        }
+
+        /// same as [`fetch_all_files`](Self::fetch_all_files) but parses the response while it downloads, one item at a time
+        pub fn stream_all_files(&self) -> super::stream::JsonStream<File> {
+            self.client.stream("fetchallfiles", "files")
         }
-        Err(json["message"].to_string())
     }
 }
 
 pub mod variables {
     use std::collections::HashMap;
-    use serde_json::Value;
 
     pub fn create(sellerkey: &str, url: String, name: &str, value: &str, authed: bool) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "addvar");
-        req_data.insert("name", name);
-        req_data.insert("data", value);
-        let authed = if authed { "1" } else { "0" };
-        req_data.insert("authed", authed);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).variables().create(name, value, authed)
     }
 
-    pub fn edit(sellerkey: &str, url: String, name: &str, value: &str, ) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "editvar");
-        req_data.insert("varid", name);
-        req_data.insert("data", value);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+    pub fn edit(sellerkey: &str, url: String, name: &str, value: &str) -> Result<String, String> {
+        super::SellerClient::new(sellerkey, url).variables().edit(name, value)
     }
 
     pub fn retrieve(sellerkey: &str, url: String, name: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "retrvvar");
-        req_data.insert("name", name);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).variables().retrieve(name)
     }
 
-    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
     pub struct Variable {
         pub varid: String,
         pub msg: String,
-        pub authed: String,
+        /// only logged in users can read it
+        #[serde(default, deserialize_with = "crate::fields::flag")]
+        pub authed: bool,
     }
 
     pub fn fetch_all(sellerkey: &str, url: String) -> Result<Vec<Variable>, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "fetchallvars");
+        super::SellerClient::new(sellerkey, url).variables().fetch_all()
+    }
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["vars"].as_array().unwrap().iter().map(|x| serde_json::from_value(x.clone()).unwrap()).collect());
-        }
-        Err(json["message"].to_string())
+    pub fn stream_all(sellerkey: &str, url: String) -> super::stream::JsonStream<Variable> {
+        super::SellerClient::new(sellerkey, url).variables().stream_all()
     }
 
     pub fn delete(sellerkey: &str, url: String, name: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "delvar");
-        req_data.insert("name", name);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).variables().delete(name)
     }
 
     pub fn delete_all(sellerkey: &str, url: String) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "delallvars");
+        super::SellerClient::new(sellerkey, url).variables().delete_all()
+    }
+
+    /// variables endpoints of a [`SellerClient`](super::SellerClient), returned by [`SellerClient::variables`](super::SellerClient::variables)
+    pub struct VariablesApi<'a> {
+        pub(super) client: &'a super::SellerClient,
+    }
+
+    impl VariablesApi<'_> {
+        pub fn create(&self, name: &str, value: &str, authed: bool) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "addvar");
+            req_data.insert("name", name);
+            req_data.insert("data", value);
+            let authed = if authed { "1" } else { "0" };
+            req_data.insert("authed", authed);
+
+            self.client.message(req_data)
+        }
+
+        pub fn edit(&self, name: &str, value: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "editvar");
+            req_data.insert("varid", name);
+            req_data.insert("data", value);
+
+            self.client.message(req_data)
+        }
+
+        pub fn retrieve(&self, name: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "retrvvar");
+            req_data.insert("name", name);
+
+            self.client.message(req_data)
+        }
+
+        pub fn fetch_all(&self) -> Result<Vec<Variable>, String> {
+            self.client.fetch_list("fetchallvars", "vars")
+        }
+
+        /// same as [`fetch_all`](Self::fetch_all) but parses the response while it downloads, one item at a time
+        pub fn stream_all(&self) -> super::stream::JsonStream<Variable> {
+            self.client.stream("fetchallvars", "vars")
+        }
+
+        pub fn delete(&self, name: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "delvar");
+            req_data.insert("name", name);
+
+            self.client.message(req_data)
+        }
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
+        pub fn delete_all(&self) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "delallvars");
+
+            self.client.message(req_data)
         }
-        Err(json["message"].to_string())
     }
 }
 
 pub mod blacklists {
     use std::collections::HashMap;
-    use serde_json::Value;
 
     pub fn add(sellerkey: &str, url: String, ip: Option<&str>, hwid: Option<&str>) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "black");
-        if ip.is_some() {
-            req_data.insert("ip", ip.unwrap());
-        }
-        if hwid.is_some() {
-            req_data.insert("hwid", hwid.unwrap());
-        }
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).blacklists().add(ip, hwid)
     }
 
     /// blacktype can be "ip" or "hwid"
     pub fn delete(sellerkey: &str, url: String, data: &str, blacktype: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "delblack");
-        req_data.insert("data", data);
-        req_data.insert("blacktype", blacktype);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).blacklists().delete(data, blacktype)
     }
 
     pub fn delete_all(sellerkey: &str, url: String) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "delblacks");
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).blacklists().delete_all()
     }
 
-    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
     pub struct Blacklist {
+        #[serde(default, deserialize_with = "crate::fields::optional_string")]
         pub hwid: Option<String>,
+        #[serde(default, deserialize_with = "crate::fields::optional_string")]
         pub ip: Option<String>,
+        /// "hwid" or "ip"
         #[serde(rename = "type")]
         pub typee: String,
     }
 
     pub fn fetch_all(sellerkey: &str, url: String) -> Result<Vec<Blacklist>, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "fetchallblacks");
+        super::SellerClient::new(sellerkey, url).blacklists().fetch_all()
+    }
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["blacklists"].as_array().unwrap().iter().map(|x| serde_json::from_value(x.clone()).unwrap()).collect());
-        }
-        Err(json["message"].to_string())
+    pub fn stream_all(sellerkey: &str, url: String) -> super::stream::JsonStream<Blacklist> {
+        super::SellerClient::new(sellerkey, url).blacklists().stream_all()
     }
 
     pub fn add_whitelist(sellerkey: &str, url: String, ip: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "addWhite");
-        req_data.insert("ip", ip);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).blacklists().add_whitelist(ip)
     }
 
     pub fn del_whitelist(sellerkey: &str, url: String, ip: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "delWhite");
-        req_data.insert("ip", ip);
+        super::SellerClient::new(sellerkey, url).blacklists().del_whitelist(ip)
+    }
+
+    /// blacklists endpoints of a [`SellerClient`](super::SellerClient), returned by [`SellerClient::blacklists`](super::SellerClient::blacklists)
+    pub struct BlacklistsApi<'a> {
+        pub(super) client: &'a super::SellerClient,
+    }
+
+    impl BlacklistsApi<'_> {
+        pub fn add(&self, ip: Option<&str>, hwid: Option<&str>) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "black");
+            if ip.is_some() {
+               req_data.insert("ip", ip.unwrap());
+            }
+            if hwid.is_some() {
+               req_data.insert("hwid", hwid.unwrap());
+            }
+
+            self.client.message(req_data)
+        }
+
+        /// blacktype can be "ip" or "hwid"
+        pub fn delete(&self, data: &str, blacktype: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "delblack");
+            req_data.insert("data", data);
+            req_data.insert("blacktype", blacktype);
+
+            self.client.message(req_data)
+        }
+
+        pub fn delete_all(&self) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "delblacks");
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
+            self.client.message(req_data)
+        }
+
+        pub fn fetch_all(&self) -> Result<Vec<Blacklist>, String> {
+            self.client.fetch_list("fetchallblacks", "blacklists")
+        }
+
+        /// same as [`fetch_all`](Self::fetch_all) but parses the response while it downloads, one item at a time
+        pub fn stream_all(&self) -> super::stream::JsonStream<Blacklist> {
+            self.client.stream("fetchallblacks", "blacklists")
+        }
+
+        pub fn add_whitelist(&self, ip: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "addWhite");
+            req_data.insert("ip", ip);
+
+            self.client.message(req_data)
+        }
+
+        pub fn del_whitelist(&self, ip: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "delWhite");
+            req_data.insert("ip", ip);
+
+            self.client.message(req_data)
         }
-        Err(json["message"].to_string())
     }
 }
 
 pub mod settings {
     use std::collections::HashMap;
-    use serde_json::Value;
 
     pub fn retrieve(sellerkey: &str, url: String) -> Result<Settings, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "getsettings");
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(serde_json::from_value(json.clone()).unwrap());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).settings().retrieve()
     }
 
-    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
     pub struct Settings {
         pub enabled: bool,
         #[serde(rename = "hwid-lock")]
@@ -1356,201 +1855,309 @@ pub mod settings {
         pub cooldown: String,
     }
 
+    macro_rules! settings_patch {
+        ($($field:ident: $ty:ty => $param:literal, $name:literal;)*) => {
+            /// only the settings that should change, None fields are left as they are.
+            /// field names are the ones of [`Settings`], build one by hand, with [`SettingsPatch::set`] or with [`Settings::diff`]
+            #[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+            pub struct SettingsPatch {
+                $(
+                    #[serde(default, rename = $name, skip_serializing_if = "Option::is_none")]
+                    pub $field: Option<$ty>,
+                )*
+            }
+
+            impl SettingsPatch {
+                /// sets a setting by the name it has in the json of [`Settings`], e.g. "hwid-lock" or "disabledmsg"
+                pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+                    match name {
+                        $($name => self.$field = Some(value.parse().map_err(|_| format!("invalid value for {}: {}", name, value))?),)*
+                        _ => return Err(format!("unknown setting {}", name)),
+                    }
+                    Ok(())
+                }
+
+                /// names of the settings this patch changes, like in [`SettingsPatch::set`]
+                pub fn changed(&self) -> Vec<&'static str> {
+                    let mut names = Vec::new();
+                    $(if self.$field.is_some() { names.push($name); })*
+                    names
+                }
+
+                pub fn is_empty(&self) -> bool {
+                    self.changed().is_empty()
+                }
+
+                pub fn apply_to(&self, settings: &mut Settings) {
+                    $(if let Some(value) = &self.$field { settings.$field = value.clone(); })*
+                }
+
+                /// keyauth's updatesettings parameters for the changed settings
+                pub(crate) fn params(&self) -> Vec<(&'static str, String)> {
+                    let mut params = Vec::new();
+                    $(if let Some(value) = &self.$field { params.push(($param, value.to_string())); })*
+                    params
+                }
+            }
+
+            impl Settings {
+                /// the patch that turns self into other
+                pub fn diff(&self, other: &Settings) -> SettingsPatch {
+                    SettingsPatch {
+                        $($field: (self.$field != other.$field).then(|| other.$field.clone()),)*
+                    }
+                }
+            }
+
+            /// every setting, what update sends
+            impl From<Settings> for SettingsPatch {
+                fn from(settings: Settings) -> Self {
+                    SettingsPatch {
+                        $($field: Some(settings.$field),)*
+                    }
+                }
+            }
+        };
+    }
+
+    // field: type => updatesettings parameter, name in the getsettings json
+    settings_patch! {
+        enabled: bool => "enabled", "enabled";
+        hwidcheck: bool => "hwidcheck", "hwid-lock";
+        version: String => "ver", "version";
+        webdownload: String => "download", "webdownload";
+        webhook: String => "webhook", "webhook";
+        resellerstore: String => "resellerstore", "resellerstore";
+        disabledmsg: String => "appdisabled", "disabledmsg";
+        usernametakenmsg: String => "usernametaken", "usernametakenmsg";
+        licenseinvalidmsg: String => "keynotfound", "licenseinvalidmsg";
+        keytakenmsg: String => "keyused", "keytakenmsg";
+        nosubmsg: String => "nosublevel", "nosubmsg";
+        userinvalidmsg: String => "usernamenotfound", "userinvalidmsg";
+        passinvalidmsg: String => "passmismatch", "passinvalidmsg";
+        hwidmismatchmsg: String => "hwidmismatch", "hwidmismatchmsg";
+        noactivesubmsg: String => "noactivesubs", "noactivesubmsg";
+        blackedmsg: String => "hwidblacked", "blackedmsg";
+        pausedmsg: String => "keypaused", "pausedmsg";
+        expiredmsg: String => "keyexpired", "expiredmsg";
+        sellixsecret: String => "sellixsecret", "sellixsecret";
+        dayresellerproductid: String => "dayproduct", "dayresellerproductid";
+        weekresellerproductid: String => "weekproduct", "weekresellerproductid";
+        monthresellerproductid: String => "monthproduct", "monthresellerproductid";
+        liferesellerproductid: String => "lifetimeproduct", "liferesellerproductid";
+    }
+
+    /// sends every setting, use patch to only change some
     pub fn update(sellerkey: &str, url: String, settings: Settings) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "updatesettings");
-        let enabled = settings.enabled.to_string();
-        req_data.insert("enabled", enabled.as_str());
-        let hwidcheck = settings.hwidcheck.to_string();
-        req_data.insert("hwidcheck", hwidcheck.as_str());
-        req_data.insert("ver", settings.version.as_str());
-        req_data.insert("download", settings.webdownload.as_str());
-        req_data.insert("webhook", settings.webhook.as_str());
-        req_data.insert("resellerstore", settings.resellerstore.as_str());
-        req_data.insert("appdisabled", settings.disabledmsg.as_str());
-        req_data.insert("usernametaken", settings.usernametakenmsg.as_str());
-        req_data.insert("keynotfound", settings.licenseinvalidmsg.as_str());
-        req_data.insert("keyused", settings.keytakenmsg.as_str());
-        req_data.insert("nosublevel", settings.nosubmsg.as_str());
-        req_data.insert("usernamenotfound", settings.userinvalidmsg.as_str());
-        req_data.insert("passmismatch", settings.passinvalidmsg.as_str());
-        req_data.insert("hwidmismatch", settings.hwidmismatchmsg.as_str());
-        req_data.insert("noactivesubs", settings.noactivesubmsg.as_str());
-        req_data.insert("hwidblacked", settings.blackedmsg.as_str());
-        req_data.insert("keypaused", settings.pausedmsg.as_str());
-        req_data.insert("keyexpired", settings.expiredmsg.as_str());
-        req_data.insert("sellixsecret", settings.sellixsecret.as_str());
-        req_data.insert("dayproduct", settings.dayresellerproductid.as_str());
-        req_data.insert("weekprocuct", settings.weekresellerproductid.as_str());
-        req_data.insert("monthproduct", settings.liferesellerproductid.as_str());
-        req_data.insert("lifetimeproduct", settings.liferesellerproductid.as_str());
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).settings().update(settings)
     }
 
-    pub fn reset_hash(sellerkey: &str, url: String) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "resethash");
+    pub fn patch(sellerkey: &str, url: String, patch: &SettingsPatch) -> Result<String, String> {
+        super::SellerClient::new(sellerkey, url).settings().patch(patch)
+    }
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+    pub fn reset_hash(sellerkey: &str, url: String) -> Result<String, String> {
+        super::SellerClient::new(sellerkey, url).settings().reset_hash()
     }
 
     pub fn add_hash(sellerkey: &str, url: String, hash: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "addhash");
-        req_data.insert("hash", hash);
+        super::SellerClient::new(sellerkey, url).settings().add_hash(hash)
+    }
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+    /// adds the [`crate::file_hash`] of a release build, use it in the release pipeline so clients can use init_with_self_hash
+    pub fn add_file_hash<P: AsRef<std::path::Path>>(sellerkey: &str, url: String, path: P) -> Result<String, String> {
+        super::SellerClient::new(sellerkey, url).settings().add_file_hash(path)
     }
 
     pub fn pause(sellerkey: &str, url: String) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "pauseapp");
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).settings().pause()
     }
 
     pub fn unpause(sellerkey: &str, url: String) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "unpauseapp");
+        super::SellerClient::new(sellerkey, url).settings().unpause()
+    }
+
+    /// settings endpoints of a [`SellerClient`](super::SellerClient), returned by [`SellerClient::settings`](super::SellerClient::settings)
+    pub struct SettingsApi<'a> {
+        pub(super) client: &'a super::SellerClient,
+    }
+
+    impl SettingsApi<'_> {
+        pub fn retrieve(&self) -> Result<Settings, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "getsettings");
+
+            let json = self.client.json(req_data)?;
+            serde_json::from_value(json).map_err(|e| e.to_string())
+        }
+
+        /// sends every setting, use patch to only change some
+        pub fn update(&self, settings: Settings) -> Result<String, String> {
+            self.patch(&SettingsPatch::from(settings))
+        }
+
+        /// changes only the settings set in the patch
+        pub fn patch(&self, patch: &SettingsPatch) -> Result<String, String> {
+            if patch.is_empty() {
+                return Err("nothing to change".to_string());
+            }
+            let params = patch.params();
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "updatesettings");
+            for (param, value) in &params {
+                req_data.insert(param, value.as_str());
+            }
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
+            self.client.message(req_data)
+        }
+
+        pub fn reset_hash(&self) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "resethash");
+
+            self.client.message(req_data)
+        }
+
+        pub fn add_hash(&self, hash: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "addhash");
+            req_data.insert("hash", hash);
+
+            self.client.message(req_data)
+        }
+
+        /// adds the [`crate::file_hash`] of a release build, use it in the release pipeline so clients can use init_with_self_hash
+        pub fn add_file_hash<P: AsRef<std::path::Path>>(&self, path: P) -> Result<String, String> {
+            let hash = crate::file_hash(path)?;
+            self.add_hash(&hash)
+        }
+
+        pub fn pause(&self) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "pauseapp");
+
+            self.client.message(req_data)
+        }
+
+        pub fn unpause(&self) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "unpauseapp");
+
+            self.client.message(req_data)
         }
-        Err(json["message"].to_string())
     }
 }
 
 pub mod account {
     use std::collections::HashMap;
-    use serde_json::Value;
 
     /// role can be eiether "Manager" or "Reseller", keylevles should be coma separated keys e.g. 1,4,8, perms look at docs https://docs.keyauth.cc/seller/accounts
     pub fn create(sellerkey: &str, url: String, role: &str, pass: &str, keylevels: Option<&str>, email: &str, perms: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "addAccount");
-        req_data.insert("role", role);
-        req_data.insert("pass", pass);
-        req_data.insert("email", email);
-        req_data.insert("perms", perms);
-        if let Some(keylevels) = keylevels {
-            req_data.insert("keylevels", keylevels);
-        }
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).account().create(role, pass, keylevels, email, perms)
     }
 
     pub fn delete(sellerkey: &str, url: String, user: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "deleteAccount");
-        req_data.insert("user", user);
+        super::SellerClient::new(sellerkey, url).account().delete(user)
+    }
+
+    /// account endpoints of a [`SellerClient`](super::SellerClient), returned by [`SellerClient::account`](super::SellerClient::account)
+    pub struct AccountApi<'a> {
+        pub(super) client: &'a super::SellerClient,
+    }
+
+    impl AccountApi<'_> {
+        /// role can be eiether "Manager" or "Reseller", keylevles should be coma separated keys e.g. 1,4,8, perms look at docs https://docs.keyauth.cc/seller/accounts
+        pub fn create(&self, role: &str, pass: &str, keylevels: Option<&str>, email: &str, perms: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "addAccount");
+            req_data.insert("role", role);
+            req_data.insert("pass", pass);
+            req_data.insert("email", email);
+            req_data.insert("perms", perms);
+            if let Some(keylevels) = keylevels {
+               req_data.insert("keylevels", keylevels);
+            }
+
+            self.client.message(req_data)
+        }
+
+        pub fn delete(&self, user: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "deleteAccount");
+            req_data.insert("user", user);
 
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
+            self.client.message(req_data)
         }
-        Err(json["message"].to_string())
     }
 }
 
 pub mod web_loader {
     use std::collections::HashMap;
-    use serde_json::Value;
 
-    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
     pub struct WebLoaderButton {
         pub text: String,
         pub value: String,
     }
 
     pub fn retrieve_all_buttons(sellerkey: &str, url: String) -> Result<Vec<WebLoaderButton>, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "fetchallbuttons");
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            if json["buttons"].is_string() {
-                return Ok(Vec::new());
-            }
-            return Ok(json["buttons"].as_array().unwrap().iter().map(|x| serde_json::from_value(x.clone()).unwrap()).collect());
-        }
-        Err(json["message"].to_string())
+        super::SellerClient::new(sellerkey, url).web_loader().retrieve_all_buttons()
+    }
+
+    pub fn stream_all_buttons(sellerkey: &str, url: String) -> super::stream::JsonStream<WebLoaderButton> {
+        super::SellerClient::new(sellerkey, url).web_loader().stream_all_buttons()
     }
 
     /// dont use spaces in value
     pub fn add_button(sellerkey: &str, url: String, value: &str, text: &str) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("sellerkey", sellerkey);
-        req_data.insert("type", "addbutton");
-        req_data.insert("value", value);
-        req_data.insert("text", text);
-
-        let res = super::request(req_data, url);
-        let resp = res.text().unwrap();
-        let json: Value = serde_json::from_str(&resp).unwrap();
-        if json["success"].as_bool().unwrap() {
-            return Ok(json["message"].to_string());
-        }
-        Err(json["message"].to_string())
-    }
-
-     pub fn delete_button(sellerkey: &str, url: String, value: &str) -> Result<String, String> {
-         let mut req_data = HashMap::new();
-         req_data.insert("sellerkey", sellerkey);
-         req_data.insert("type", "delbutton");
-         req_data.insert("value", value);
-
-         let res = super::request(req_data, url);
-         let resp = res.text().unwrap();
-         let json: Value = serde_json::from_str(&resp).unwrap();
-         if json["success"].as_bool().unwrap() {
-             return Ok(json["message"].to_string());
-         }
-         Err(json["message"].to_string())
-     }
-}
\ No newline at end of file
+        super::SellerClient::new(sellerkey, url).web_loader().add_button(value, text)
+    }
+
+    pub fn delete_button(sellerkey: &str, url: String, value: &str) -> Result<String, String> {
+        super::SellerClient::new(sellerkey, url).web_loader().delete_button(value)
+    }
+
+    /// web loader endpoints of a [`SellerClient`](super::SellerClient), returned by [`SellerClient::web_loader`](super::SellerClient::web_loader)
+    pub struct WebLoaderApi<'a> {
+        pub(super) client: &'a super::SellerClient,
+    }
+
+    impl WebLoaderApi<'_> {
+        pub fn retrieve_all_buttons(&self) -> Result<Vec<WebLoaderButton>, String> {
+            self.client.fetch_list("fetchallbuttons", "buttons")
+        }
+
+        /// same as [`retrieve_all_buttons`](Self::retrieve_all_buttons) but parses the response while it downloads, one item at a time
+        pub fn stream_all_buttons(&self) -> super::stream::JsonStream<WebLoaderButton> {
+            self.client.stream("fetchallbuttons", "buttons")
+        }
+
+        /// dont use spaces in value
+        pub fn add_button(&self, value: &str, text: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "addbutton");
+            req_data.insert("value", value);
+            req_data.insert("text", text);
+
+            self.client.message(req_data)
+        }
+
+        pub fn delete_button(&self, value: &str) -> Result<String, String> {
+            let mut req_data = HashMap::new();
+            req_data.insert("sellerkey", self.client.key.as_str());
+            req_data.insert("type", "delbutton");
+            req_data.insert("value", value);
+
+            self.client.message(req_data)
+        }
+    }
+}
@@ -0,0 +1,408 @@
+/*!
+snapshot a whole application into one file and restore it into another one, e.g. staging to production
+
+```rust,no_run
+use keyauth::seller::{SellerClient, backup::{Backup, RestoreOptions}};
+let staging = SellerClient::new("staging sellerkey", "https://keyauth.win/api/seller/".to_string());
+let production = SellerClient::new("production sellerkey", "https://keyauth.win/api/seller/".to_string());
+
+let backup = Backup::snapshot(&staging).unwrap();
+backup.save("staging.keyauth-backup.json").unwrap();
+
+let backup = Backup::load("staging.keyauth-backup.json").unwrap();
+let plan = backup.restore(&production, RestoreOptions { dry_run: true, overwrite: false }).unwrap();
+for change in &plan.changes {
+    println!("{:?} {} {:?}", change.difference.section, change.difference.name, change.outcome);
+}
+```
+
+restoring never deletes anything from the target and only changes existing entries with `overwrite`.
+users are created without a password (they set it on their first login) and used licenses are not recreated,
+their users are restored instead. keyauth only creates licenses from a mask, so keys containing `X`, `x` or `*` fail to restore
+*/
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::SystemTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use super::{empty_if_none_found, SellerClient};
+use super::licenses::{License, LicenseSpec, LicenseStatus};
+
+/// version of the archive format written by this version of the library
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+pub struct UserBackup {
+    /// password is always None in a backup
+    pub user: super::user::User,
+    /// subscriptions and hwid
+    pub data: Option<super::user::UserData>,
+    pub vars: Vec<super::user::UserVar>,
+}
+
+/// everything the seller api can read from an application
+#[derive(Serialize, Deserialize)]
+pub struct Backup {
+    pub version: u32,
+    #[serde(deserialize_with = "crate::fields::unix_timestamp", serialize_with = "crate::fields::serialize_unix_timestamp")]
+    pub created: SystemTime,
+    pub settings: Option<super::settings::Settings>,
+    pub subscriptions: Vec<super::subscriptions::Sub>,
+    pub licenses: Vec<License>,
+    pub users: Vec<UserBackup>,
+    pub variables: Vec<super::variables::Variable>,
+    pub blacklists: Vec<super::blacklists::Blacklist>,
+    pub channels: Vec<super::chat::Chat>,
+    pub buttons: Vec<super::web_loader::WebLoaderButton>,
+}
+
+impl Backup {
+    /// reads everything from the app of client, Err if any section couldnt be read.
+    /// keyauth's "no ... found" answer for an empty list is an empty section, not an error
+    pub fn snapshot(client: &SellerClient) -> Result<Backup, String> {
+        let section = |name: &str, err: String| format!("{}: {}", name, err);
+        let settings = client.settings().retrieve().map_err(|e| section("settings", e))?;
+        let subscriptions = empty_if_none_found(client.subscriptions().fetch_all()).map_err(|e| section("subscriptions", e))?;
+        let licenses = empty_if_none_found(client.licenses().fetch_all()).map_err(|e| section("licenses", e))?;
+        let variables = empty_if_none_found(client.variables().fetch_all()).map_err(|e| section("variables", e))?;
+        let blacklists = empty_if_none_found(client.blacklists().fetch_all()).map_err(|e| section("blacklists", e))?;
+        let channels = empty_if_none_found(client.chat().fetch_all_channels()).map_err(|e| section("chat channels", e))?;
+        let buttons = empty_if_none_found(client.web_loader().retrieve_all_buttons()).map_err(|e| section("web loader buttons", e))?;
+
+        let mut vars: HashMap<String, Vec<super::user::UserVar>> = HashMap::new();
+        for var in empty_if_none_found(client.users().fetch_all_vars()).map_err(|e| section("user variables", e))? {
+            vars.entry(var.user.clone()).or_default().push(var);
+        }
+        let mut users = Vec::new();
+        for mut user in empty_if_none_found(client.users().fetch_all_users()).map_err(|e| section("users", e))? {
+            user.password = None;
+            let data = client.users().user_data(&user.username).map_err(|e| section(&format!("user {}", user.username), e))?;
+            let vars = vars.remove(&user.username).unwrap_or_default();
+            users.push(UserBackup { user, data: Some(data), vars });
+        }
+
+        Ok(Backup {
+            version: FORMAT_VERSION,
+            created: SystemTime::now(),
+            settings: Some(settings),
+            subscriptions,
+            licenses,
+            users,
+            variables,
+            blacklists,
+            channels,
+            buttons,
+        })
+    }
+
+    pub fn write_to<W: Write>(&self, writer: W) -> Result<(), String> {
+        serde_json::to_writer_pretty(writer, self).map_err(|e| e.to_string())
+    }
+
+    /// Err if the archive was written by a newer version of the format
+    pub fn read_from<R: Read>(reader: R) -> Result<Backup, String> {
+        let value: Value = serde_json::from_reader(reader).map_err(|e| e.to_string())?;
+        let version = value["version"].as_u64().ok_or("not a keyauth backup, version is missing")?;
+        if version > FORMAT_VERSION as u64 {
+            return Err(format!("backup format version {} is newer than the supported version {}", version, FORMAT_VERSION));
+        }
+        serde_json::from_value(value).map_err(|e| e.to_string())
+    }
+
+    /// the archive contains keys and user data, keep it somewhere safe
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+        self.write_to(std::io::BufWriter::new(file))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Backup, String> {
+        let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+        Backup::read_from(std::io::BufReader::new(file))
+    }
+
+    /// what is different in other compared to self, e.g. self is the backup and other a snapshot of the target
+    pub fn diff(&self, other: &Backup) -> Vec<Difference> {
+        let mut differences = Vec::new();
+        let settings = |b: &Backup| b.settings.iter().map(|s| ("settings".to_string(), value(s))).collect::<Vec<_>>();
+        compare(Section::Settings, settings(self), settings(other), &["cooldown"], &mut differences);
+        compare(Section::Subscriptions, keyed(&self.subscriptions, |s| s.name.clone()), keyed(&other.subscriptions, |s| s.name.clone()), &[], &mut differences);
+        compare(Section::Licenses, keyed(&self.licenses, |l| l.key.clone()), keyed(&other.licenses, |l| l.key.clone()), &["id", "app", "genby", "gendate"], &mut differences);
+        compare(Section::Users, keyed(&self.users, |u| u.user.username.clone()), keyed(&other.users, |u| u.user.username.clone()), &["id", "app", "owner", "createdate", "lastlogin", "ip", "password", "token", "cooldown"], &mut differences);
+        compare(Section::Variables, keyed(&self.variables, |v| v.varid.clone()), keyed(&other.variables, |v| v.varid.clone()), &[], &mut differences);
+        compare(Section::Blacklists, keyed(&self.blacklists, blacklist_key), keyed(&other.blacklists, blacklist_key), &[], &mut differences);
+        compare(Section::ChatChannels, keyed(&self.channels, |c| c.name.clone()), keyed(&other.channels, |c| c.name.clone()), &[], &mut differences);
+        compare(Section::WebLoaderButtons, keyed(&self.buttons, |b| b.value.clone()), keyed(&other.buttons, |b| b.value.clone()), &[], &mut differences);
+        differences
+    }
+
+    /// restores the backup into the app of client, see the module docs for what can be restored
+    pub fn restore(&self, client: &SellerClient, options: RestoreOptions) -> Result<RestoreReport, String> {
+        let current = Backup::snapshot(client)?;
+        let mut report = RestoreReport::default();
+        for difference in self.diff(&current) {
+            let outcome = match difference.kind {
+                DifferenceKind::Extra => Outcome::Kept,
+                DifferenceKind::Changed(_) if !options.overwrite => Outcome::Conflict,
+                _ if options.dry_run => Outcome::Planned,
+                DifferenceKind::Missing => self.create(client, &difference),
+                DifferenceKind::Changed(_) => self.update(client, &difference),
+            };
+            report.changes.push(Change { difference, outcome });
+        }
+        Ok(report)
+    }
+
+    fn create(&self, client: &SellerClient, difference: &Difference) -> Outcome {
+        let name = difference.name.as_str();
+        let result = match difference.section {
+            Section::Settings => return self.update(client, difference),
+            Section::Subscriptions => find(&self.subscriptions, name, |s| s.name.clone())
+                .and_then(|sub| client.subscriptions().create(&sub.name, &sub.level.to_string()).map(drop)),
+            Section::Licenses => match find(&self.licenses, name, |l| l.key.clone()) {
+                Ok(license) if license.status != LicenseStatus::NotUsed && license.status != LicenseStatus::Banned => {
+                    return Outcome::Skipped("used licenses cant be recreated, their user is restored instead".to_string());
+                }
+                Ok(license) => restore_license(client, license),
+                Err(err) => Err(err),
+            },
+            Section::Users => find(&self.users, name, |u| u.user.username.clone())
+                .and_then(|user| restore_user(client, user)),
+            Section::Variables => find(&self.variables, name, |v| v.varid.clone())
+                .and_then(|var| client.variables().create(&var.varid, &var.msg, var.authed).map(drop)),
+            Section::Blacklists => find(&self.blacklists, name, blacklist_key)
+                .and_then(|entry| client.blacklists().add(entry.ip.as_deref(), entry.hwid.as_deref()).map(drop)),
+            Section::ChatChannels => find(&self.channels, name, |c| c.name.clone())
+                .and_then(|channel| client.chat().create_channel(&channel.name, &channel.delay.to_string()).map(drop)),
+            Section::WebLoaderButtons => find(&self.buttons, name, |b| b.value.clone())
+                .and_then(|button| client.web_loader().add_button(&button.value, &button.text).map(drop)),
+        };
+        outcome(result)
+    }
+
+    fn update(&self, client: &SellerClient, difference: &Difference) -> Outcome {
+        let name = difference.name.as_str();
+        let result = match difference.section {
+            Section::Settings => match &self.settings {
+                Some(settings) => client.settings().update(settings.clone()).map(drop),
+                None => return Outcome::Skipped("the backup has no settings".to_string()),
+            },
+            Section::Subscriptions => find(&self.subscriptions, name, |s| s.name.clone())
+                .and_then(|sub| client.subscriptions().edit(&sub.name, &sub.level.to_string()).map(drop)),
+            Section::Variables => find(&self.variables, name, |v| v.varid.clone())
+                .and_then(|var| client.variables().edit(&var.varid, &var.msg).map(drop)),
+            Section::ChatChannels => find(&self.channels, name, |c| c.name.clone())
+                .and_then(|channel| client.chat().edit_channel(&channel.name, &channel.delay.to_string()).map(drop)),
+            Section::WebLoaderButtons => find(&self.buttons, name, |b| b.value.clone())
+                .and_then(|button| client.web_loader().delete_button(&button.value)
+                    .and_then(|_| client.web_loader().add_button(&button.value, &button.text)).map(drop)),
+            Section::Licenses | Section::Users | Section::Blacklists => return Outcome::Conflict,
+        };
+        match outcome(result) {
+            Outcome::Created => Outcome::Updated,
+            other => other,
+        }
+    }
+}
+
+/// the entry of a difference, Err if the backup doesnt have it
+fn find<'a, T>(items: &'a [T], name: &str, key: impl Fn(&T) -> String) -> Result<&'a T, String> {
+    items.iter().find(|item| key(item) == name).ok_or_else(|| format!("{} isnt in the backup", name))
+}
+
+fn restore_license(client: &SellerClient, license: &License) -> Result<(), String> {
+    // keyauth can only create keys from a mask, a mask without random characters creates exactly that key
+    exact_mask(&license.key)?;
+    let spec = LicenseSpec::new(license.expires).mask(license.key.as_str()).level(license.level);
+    client.licenses().add(&spec, 1)?;
+    if let Some(note) = &license.note {
+        client.licenses().set_note(&license.key, note)?;
+    }
+    if let Some(reason) = &license.banned {
+        client.licenses().ban(&license.key, reason, None)?;
+    }
+    Ok(())
+}
+
+/// Err if key as a mask wouldnt create exactly key
+pub(crate) fn exact_mask(key: &str) -> Result<(), String> {
+    if key.is_empty() {
+        return Err("license key is empty".to_string());
+    }
+    if let Some(c) = key.chars().find(|c| matches!(c, '*' | 'X' | 'x')) {
+        return Err(format!("license keys with {:?} cant be restored, keyauth would replace it with a random character", c));
+    }
+    if let Some(c) = key.chars().find(|c| !c.is_ascii_alphanumeric() && *c != '-') {
+        return Err(format!("license key contains invalid character {:?}", c));
+    }
+    Ok(())
+}
+
+fn restore_user(client: &SellerClient, backup: &UserBackup) -> Result<(), String> {
+    let name = backup.user.username.as_str();
+    let now = SystemTime::now();
+    let subscriptions: Vec<(&str, u64)> = backup.data.iter()
+        .flat_map(|d| &d.subscriptions)
+        .filter_map(|s| {
+            let left = s.expiry.duration_since(now).ok()?;
+            Some((s.subscription.as_str(), left.as_secs().div_ceil(86400)))
+        })
+        .collect();
+    let Some(&(sub, days)) = subscriptions.first() else {
+        return Err("user has no active subscription to create it with".to_string());
+    };
+    client.users().create(name, sub, days, None)?;
+    for &(sub, days) in &subscriptions[1..] {
+        client.users().extend_user_subscription(name, sub, &days.to_string(), None)?;
+    }
+    for var in &backup.vars {
+        client.users().set_var(name, &var.name, &var.data)?;
+    }
+    if let Some(hwid) = backup.data.as_ref().and_then(|d| d.hwid.as_deref()).filter(|h| !h.is_empty()) {
+        client.users().add_hwid(name, hwid)?;
+    }
+    if let Some(reason) = backup.user.banned.as_deref().filter(|r| !r.is_empty()) {
+        client.users().ban(name, Some(reason.to_string()))?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RestoreOptions {
+    /// only report what would be done
+    pub dry_run: bool,
+    /// update entries that exist in the target but differ, where the api allows it
+    pub overwrite: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Section {
+    Settings,
+    Subscriptions,
+    Licenses,
+    Users,
+    Variables,
+    Blacklists,
+    ChatChannels,
+    WebLoaderButtons,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DifferenceKind {
+    /// in the backup but not in the target
+    Missing,
+    /// in both but different, with the paths of the fields that differ
+    Changed(Vec<String>),
+    /// only in the target
+    Extra,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    pub section: Section,
+    /// key, username, variable name, ... of the entry
+    pub name: String,
+    pub kind: DifferenceKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// dry run, would be created or updated
+    Planned,
+    Created,
+    Updated,
+    /// exists in the target with different values and wasnt changed
+    Conflict,
+    /// only in the target, restore never deletes
+    Kept,
+    Skipped(String),
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub difference: Difference,
+    pub outcome: Outcome,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestoreReport {
+    pub changes: Vec<Change>,
+}
+
+impl RestoreReport {
+    pub fn conflicts(&self) -> impl Iterator<Item = &Change> {
+        self.changes.iter().filter(|c| c.outcome == Outcome::Conflict)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &Change> {
+        self.changes.iter().filter(|c| matches!(c.outcome, Outcome::Failed(_)))
+    }
+}
+
+fn outcome(result: Result<(), String>) -> Outcome {
+    match result {
+        Ok(()) => Outcome::Created,
+        Err(err) => Outcome::Failed(err),
+    }
+}
+
+fn blacklist_key(entry: &super::blacklists::Blacklist) -> String {
+    format!("{} {}", entry.typee, entry.hwid.as_deref().or(entry.ip.as_deref()).unwrap_or_default())
+}
+
+fn value<T: Serialize>(item: &T) -> Value {
+    serde_json::to_value(item).unwrap_or(Value::Null)
+}
+
+fn keyed<T: Serialize>(items: &[T], key: impl Fn(&T) -> String) -> Vec<(String, Value)> {
+    items.iter().map(|item| (key(item), value(item))).collect()
+}
+
+fn compare(section: Section, ours: Vec<(String, Value)>, theirs: Vec<(String, Value)>, ignore: &[&str], out: &mut Vec<Difference>) {
+    let mut theirs: BTreeMap<String, Value> = theirs.into_iter().collect();
+    for (name, ours) in ours {
+        let kind = match theirs.remove(&name) {
+            None => DifferenceKind::Missing,
+            Some(theirs) => {
+                let changed = changed_fields(&ours, &theirs, ignore);
+                if changed.is_empty() {
+                    continue;
+                }
+                DifferenceKind::Changed(changed)
+            }
+        };
+        out.push(Difference { section, name, kind });
+    }
+    out.extend(theirs.into_keys().map(|name| Difference { section, name, kind: DifferenceKind::Extra }));
+}
+
+/// paths of the fields that differ, fields named in ignore are skipped at any depth
+fn changed_fields(ours: &Value, theirs: &Value, ignore: &[&str]) -> Vec<String> {
+    let (mut a, mut b) = (BTreeMap::new(), BTreeMap::new());
+    flatten(String::new(), ours, ignore, &mut a);
+    flatten(String::new(), theirs, ignore, &mut b);
+    let mut changed: Vec<String> = a.iter().filter(|(k, v)| b.get(*k) != Some(*v)).map(|(k, _)| k.clone()).collect();
+    changed.extend(b.keys().filter(|k| !a.contains_key(*k)).cloned());
+    changed
+}
+
+fn flatten(path: String, value: &Value, ignore: &[&str], out: &mut BTreeMap<String, Value>) {
+    let join = |key: &str| if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) };
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter().filter(|(k, _)| !ignore.contains(&k.as_str())) {
+                flatten(join(key), value, ignore, out);
+            }
+        }
+        Value::Array(items) => {
+            for (i, value) in items.iter().enumerate() {
+                flatten(join(&i.to_string()), value, ignore, out);
+            }
+        }
+        _ => {
+            out.insert(path, value.clone());
+        }
+    }
+}
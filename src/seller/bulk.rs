@@ -0,0 +1,292 @@
+/*!
+runs one user operation on many users at once, with a few requests in flight, retries and progress callbacks
+
+```rust,no_run
+use std::time::Duration;
+use keyauth::seller::SellerClient;
+use keyauth::seller::bulk::{Bulk, Operation, UserSelection};
+let client = SellerClient::new("sellerkey", "https://keyauth.win/api/seller/".to_string());
+let users = UserSelection::new()
+    .subscription("trial")
+    .inactive_for(Duration::from_secs(30 * 86400))
+    .select(&client)
+    .unwrap();
+let report = Bulk::new(&client)
+    .concurrency(4)
+    .on_progress(|p| println!("{}/{} {}", p.done, p.total, p.user))
+    .run(&users, &Operation::Ban { reason: Some("inactive trial".to_string()) });
+println!("{} banned, {} failed", report.succeeded().count(), report.failed().count());
+```
+*/
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use super::SellerClient;
+use super::user::User;
+
+/// what to do with each user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    Ban { reason: Option<String> },
+    Unban,
+    ResetHwid,
+    /// adds days to a subscription, active_only skips users whose subscription ran out
+    Extend { sub: String, days: u64, active_only: bool },
+    SetVar { var: String, value: String },
+    DeleteVar { var: String },
+    Delete,
+}
+
+impl Operation {
+    /// retries a [`Bulk`] uses unless set, 0 for the operations that would change something twice
+    /// if a retried request did reach keyauth
+    pub fn default_retries(&self) -> u32 {
+        match self {
+            Operation::Extend { .. } | Operation::SetVar { .. } | Operation::Delete => 0,
+            _ => 2,
+        }
+    }
+
+    pub fn apply(&self, client: &SellerClient, user: &str) -> Result<String, String> {
+        let users = client.users();
+        match self {
+            Operation::Ban { reason } => users.ban(user, reason.clone()),
+            Operation::Unban => users.unban(user),
+            Operation::ResetHwid => users.reset_hwid(user),
+            Operation::Extend { sub, days, active_only } => users.extend_user_subscription(user, sub, &days.to_string(), Some(*active_only)),
+            Operation::SetVar { var, value } => users.set_var(user, var, value),
+            Operation::DeleteVar { var } => users.delete_var(user, var),
+            Operation::Delete => users.delete(user),
+        }
+    }
+}
+
+type UserPredicate = Box<dyn Fn(&User) -> bool + Send + Sync>;
+type ProgressCallback<'a> = Box<dyn Fn(&Progress) + Send + Sync + 'a>;
+
+/// picks users by what fetchallusers and userdata return, every condition has to match
+#[derive(Default)]
+pub struct UserSelection {
+    subscription: Option<String>,
+    inactive_for: Option<Duration>,
+    banned: Option<bool>,
+    predicate: Option<UserPredicate>,
+    concurrency: Option<usize>,
+}
+
+impl UserSelection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// users with an active subscription of this name, needs a userdata request per user
+    pub fn subscription(mut self, name: &str) -> Self {
+        self.subscription = Some(name.to_string());
+        self
+    }
+
+    /// users whose last login is at least this long ago, users that never logged in count as inactive
+    pub fn inactive_for(mut self, duration: Duration) -> Self {
+        self.inactive_for = Some(duration);
+        self
+    }
+
+    pub fn banned(mut self, banned: bool) -> Self {
+        self.banned = Some(banned);
+        self
+    }
+
+    /// any other condition on the user
+    pub fn matching<F: Fn(&User) -> bool + Send + Sync + 'static>(mut self, predicate: F) -> Self {
+        self.predicate = Some(Box::new(predicate));
+        self
+    }
+
+    /// requests in flight for the userdata lookups, 4 by default
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = Some(concurrency);
+        self
+    }
+
+    /// checks the conditions that only need the user list
+    pub fn matches_user(&self, user: &User, now: SystemTime) -> bool {
+        if let Some(inactive_for) = self.inactive_for {
+            if let Some(last) = user.lastlogin {
+                if last + inactive_for > now {
+                    return false;
+                }
+            }
+        }
+        if let Some(banned) = self.banned {
+            if user.banned.as_deref().is_some_and(|b| !b.is_empty()) != banned {
+                return false;
+            }
+        }
+        self.predicate.as_ref().is_none_or(|predicate| predicate(user))
+    }
+
+    /// usernames of the matching users
+    pub fn select(&self, client: &SellerClient) -> Result<Vec<String>, String> {
+        let now = SystemTime::now();
+        let users: Vec<String> = client.users().fetch_all_users()?
+            .into_iter()
+            .filter(|u| self.matches_user(u, now))
+            .map(|u| u.username)
+            .collect();
+        let Some(sub) = &self.subscription else {
+            return Ok(users);
+        };
+        let has_sub = Mutex::new(Vec::new());
+        let report = Bulk::new(client).concurrency(self.concurrency.unwrap_or(4)).run_with(&users, |client, user| {
+            let data = client.users().user_data(user)?;
+            let active = data.subscriptions.iter().any(|s| s.subscription == *sub && s.is_active_at(now));
+            if active {
+                has_sub.lock().unwrap().push(user.to_string());
+            }
+            Ok(String::new())
+        });
+        if let Some(failed) = report.failed().next() {
+            return Err(format!("userdata of {} failed: {}", failed.user, failed.result.as_ref().unwrap_err()));
+        }
+        // keep the order of the user list
+        let has_sub = has_sub.into_inner().unwrap();
+        Ok(users.into_iter().filter(|u| has_sub.contains(u)).collect())
+    }
+}
+
+/// passed to the progress callback after each user is done
+pub struct Progress<'a> {
+    /// users done so far, including this one
+    pub done: usize,
+    pub total: usize,
+    pub user: &'a str,
+    pub result: &'a Result<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserResult {
+    pub user: String,
+    /// 1 plus how many requests had to be sent again
+    pub attempts: u32,
+    pub result: Result<String, String>,
+}
+
+/// one result per user, in the order the users were given
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BulkReport {
+    pub results: Vec<UserResult>,
+}
+
+impl BulkReport {
+    pub fn succeeded(&self) -> impl Iterator<Item = &UserResult> {
+        self.results.iter().filter(|r| r.result.is_ok())
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &UserResult> {
+        self.results.iter().filter(|r| r.result.is_err())
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.failed().next().is_none()
+    }
+}
+
+/// runs operations for many users on a few threads
+pub struct Bulk<'a> {
+    client: &'a SellerClient,
+    concurrency: usize,
+    retries: Option<u32>,
+    retry_delay: Duration,
+    progress: Option<ProgressCallback<'a>>,
+}
+
+impl<'a> Bulk<'a> {
+    /// 4 requests at once, retries a second apart as many as [`Operation::default_retries`] (2 for run_with)
+    pub fn new(client: &'a SellerClient) -> Self {
+        Self {
+            client,
+            concurrency: 4,
+            retries: None,
+            retry_delay: Duration::from_secs(1),
+            progress: None,
+        }
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// how often a request is repeated when it fails to reach keyauth, see [`SellerClient::with_retries`].
+    /// errors keyauth answers with, like "User not found", are not retried
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = Some(retries);
+        self
+    }
+
+    /// wait before a retry, doubled for every further one
+    pub fn retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// called from the worker threads after every user
+    pub fn on_progress<F: Fn(&Progress) + Send + Sync + 'a>(mut self, progress: F) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    pub fn run(&self, users: &[String], operation: &Operation) -> BulkReport {
+        let retries = self.retries.unwrap_or_else(|| operation.default_retries());
+        self.run_retrying(users, retries, |client, user| operation.apply(client, user))
+    }
+
+    /// like run but with any function of the client and username
+    pub fn run_with<F>(&self, users: &[String], operation: F) -> BulkReport
+    where
+        F: Fn(&SellerClient, &str) -> Result<String, String> + Sync,
+    {
+        self.run_retrying(users, self.retries.unwrap_or(2), operation)
+    }
+
+    fn run_retrying<F>(&self, users: &[String], retries: u32, operation: F) -> BulkReport
+    where
+        F: Fn(&SellerClient, &str) -> Result<String, String> + Sync,
+    {
+        let next = AtomicUsize::new(0);
+        let done = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<UserResult>>> = Mutex::new(vec![None; users.len()]);
+        std::thread::scope(|scope| {
+            for _ in 0..self.concurrency.min(users.len()) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(user) = users.get(i) else {
+                        break;
+                    };
+                    let result = self.attempt(user, retries, &operation);
+                    if let Some(progress) = &self.progress {
+                        progress(&Progress {
+                            done: done.fetch_add(1, Ordering::Relaxed) + 1,
+                            total: users.len(),
+                            user,
+                            result: &result.result,
+                        });
+                    }
+                    results.lock().unwrap()[i] = Some(result);
+                });
+            }
+        });
+        BulkReport { results: results.into_inner().unwrap().into_iter().flatten().collect() }
+    }
+
+    fn attempt<F>(&self, user: &str, retries: u32, operation: &F) -> UserResult
+    where
+        F: Fn(&SellerClient, &str) -> Result<String, String> + Sync,
+    {
+        // a client per user so its retry count is only this user's
+        let client = self.client.clone().with_retries(retries, self.retry_delay);
+        let result = operation(&client, user);
+        UserResult { user: user.to_string(), attempts: client.retried() + 1, result }
+    }
+}
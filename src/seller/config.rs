@@ -0,0 +1,402 @@
+/*!
+keep the configuration of an app in a toml or yaml file and sync it with [`Config::plan`] and [`Plan::apply`]
+
+```toml
+# only the settings listed here are changed, the names are the ones getsettings returns
+[settings]
+version = "1.3"
+hwid-lock = true
+
+# name = level
+[subscriptions]
+default = 1
+vip = 2
+
+[variables]
+motd = "welcome"
+download = { value = "https://example.com/app.zip", authed = true }
+
+# name = delay in seconds
+[chat]
+general = 5
+
+# value = text
+[web_loader]
+download = "Download"
+
+whitelist = ["203.0.113.7"]
+
+# keyauth cant list webhooks, so a webhook is created when it has no id yet,
+# put the id from the apply report into the file afterwards
+[[webhooks]]
+baseurl = "https://example.com/hook"
+user_agent = "KeyAuth"
+authed = true
+```
+
+only the sections that are in the file are managed, entries of a managed section that arent in the file are deleted.
+the whitelist cant be read from keyauth either, its ips are added on every apply and `remove_whitelist` deletes ips
+
+```rust,no_run
+use keyauth::seller::{SellerClient, config::Config};
+let client = SellerClient::new("sellerkey", "https://keyauth.win/api/seller/".to_string());
+let config = Config::load("keyauth.toml").unwrap();
+let plan = config.plan(&client).unwrap();
+for action in &plan.actions {
+    println!("{}", action);
+}
+let report = plan.apply(&client);
+assert!(report.is_success());
+```
+*/
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use serde::Deserialize;
+use serde_json::Value;
+use super::{empty_if_none_found, SellerClient};
+use super::settings::{Settings, SettingsPatch};
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// settings to change, keyed like the json of getsettings
+    #[serde(default)]
+    pub settings: Option<BTreeMap<String, Value>>,
+    /// name -> level
+    #[serde(default)]
+    pub subscriptions: Option<BTreeMap<String, u32>>,
+    #[serde(default)]
+    pub variables: Option<BTreeMap<String, VariableConfig>>,
+    /// channel name -> delay in seconds
+    #[serde(default)]
+    pub chat: Option<BTreeMap<String, u64>>,
+    /// button value -> text
+    #[serde(default)]
+    pub web_loader: Option<BTreeMap<String, String>>,
+    #[serde(default)]
+    pub whitelist: Vec<String>,
+    #[serde(default)]
+    pub remove_whitelist: Vec<String>,
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+}
+
+/// a global variable, either just the value or a table with value and authed
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum VariableConfig {
+    Value(String),
+    Full {
+        value: String,
+        #[serde(default)]
+        authed: bool,
+    },
+}
+
+impl VariableConfig {
+    pub fn value(&self) -> &str {
+        match self {
+            VariableConfig::Value(value) | VariableConfig::Full { value, .. } => value,
+        }
+    }
+
+    pub fn authed(&self) -> bool {
+        matches!(self, VariableConfig::Full { authed: true, .. })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub baseurl: String,
+    #[serde(default = "default_user_agent")]
+    pub user_agent: String,
+    #[serde(default)]
+    pub authed: bool,
+    /// set once the webhook exists
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+fn default_user_agent() -> String {
+    "KeyAuth".to_string()
+}
+
+impl Config {
+    pub fn from_toml(s: &str) -> Result<Config, String> {
+        toml::from_str(s).map_err(|e| e.to_string())
+    }
+
+    pub fn from_yaml(s: &str) -> Result<Config, String> {
+        let docs = yaml_rust2::YamlLoader::load_from_str(s).map_err(|e| e.to_string())?;
+        let value = match docs.first() {
+            Some(doc) => yaml_to_json(doc)?,
+            None => Value::Object(Default::default()),
+        };
+        serde_json::from_value(value).map_err(|e| e.to_string())
+    }
+
+    /// picks the format by extension, .yaml and .yml are yaml and everything else toml
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Config, String> {
+        let path = path.as_ref();
+        let s = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml") | Some("yml") => Config::from_yaml(&s),
+            _ => Config::from_toml(&s),
+        }
+    }
+
+    /// reads the live state of the app and works out what has to change, nothing is changed yet
+    pub fn plan(&self, client: &SellerClient) -> Result<Plan, String> {
+        let settings = match self.settings {
+            Some(_) => Some(client.settings().retrieve()?),
+            None => None,
+        };
+        // keyauth answers an empty list with "no ... found", every other error stops the plan
+        let live = Live {
+            settings,
+            subscriptions: match &self.subscriptions {
+                Some(_) => empty_if_none_found(client.subscriptions().fetch_all())?.into_iter().map(|s| (s.name, s.level)).collect(),
+                None => BTreeMap::new(),
+            },
+            variables: match &self.variables {
+                Some(_) => empty_if_none_found(client.variables().fetch_all())?.into_iter().map(|v| (v.varid, (v.msg, v.authed))).collect(),
+                None => BTreeMap::new(),
+            },
+            chat: match &self.chat {
+                Some(_) => empty_if_none_found(client.chat().fetch_all_channels())?.into_iter().map(|c| (c.name, c.delay)).collect(),
+                None => BTreeMap::new(),
+            },
+            web_loader: match &self.web_loader {
+                Some(_) => empty_if_none_found(client.web_loader().retrieve_all_buttons())?.into_iter().map(|b| (b.value, b.text)).collect(),
+                None => BTreeMap::new(),
+            },
+        };
+        self.plan_against(live)
+    }
+
+    pub(crate) fn plan_against(&self, live: Live) -> Result<Plan, String> {
+        let mut actions = Vec::new();
+
+        if let (Some(wanted), Some(current)) = (&self.settings, live.settings) {
+            let mut json = serde_json::to_value(&current).map_err(|e| e.to_string())?;
+            for (key, value) in wanted {
+                let old = json.get_mut(key).ok_or_else(|| format!("unknown setting {}", key))?;
+                *old = value.clone();
+            }
+            let settings: Settings = serde_json::from_value(json).map_err(|e| format!("invalid settings: {}", e))?;
+            let patch = current.diff(&settings);
+            if !patch.is_empty() {
+                actions.push(Action::UpdateSettings(Box::new(patch)));
+            }
+        }
+
+        if let Some(wanted) = &self.subscriptions {
+            for (name, &level) in wanted {
+                match live.subscriptions.get(name) {
+                    None => actions.push(Action::CreateSubscription { name: name.clone(), level }),
+                    Some(&current) if current != level => actions.push(Action::EditSubscription { name: name.clone(), level }),
+                    Some(_) => {}
+                }
+            }
+            for name in live.subscriptions.keys().filter(|n| !wanted.contains_key(*n)) {
+                actions.push(Action::DeleteSubscription { name: name.clone() });
+            }
+        }
+
+        if let Some(wanted) = &self.variables {
+            for (name, var) in wanted {
+                let create = Action::CreateVariable { name: name.clone(), value: var.value().to_string(), authed: var.authed() };
+                match live.variables.get(name) {
+                    None => actions.push(create),
+                    // authed can only be set when creating
+                    Some((_, authed)) if *authed != var.authed() => {
+                        actions.push(Action::DeleteVariable { name: name.clone() });
+                        actions.push(create);
+                    }
+                    Some((value, _)) if value != var.value() => actions.push(Action::EditVariable { name: name.clone(), value: var.value().to_string() }),
+                    Some(_) => {}
+                }
+            }
+            for name in live.variables.keys().filter(|n| !wanted.contains_key(*n)) {
+                actions.push(Action::DeleteVariable { name: name.clone() });
+            }
+        }
+
+        if let Some(wanted) = &self.chat {
+            for (name, &delay) in wanted {
+                match live.chat.get(name) {
+                    None => actions.push(Action::CreateChannel { name: name.clone(), delay }),
+                    Some(&current) if current != delay => actions.push(Action::EditChannel { name: name.clone(), delay }),
+                    Some(_) => {}
+                }
+            }
+            for name in live.chat.keys().filter(|n| !wanted.contains_key(*n)) {
+                actions.push(Action::DeleteChannel { name: name.clone() });
+            }
+        }
+
+        if let Some(wanted) = &self.web_loader {
+            for (value, text) in wanted {
+                match live.web_loader.get(value) {
+                    None => actions.push(Action::AddButton { value: value.clone(), text: text.clone() }),
+                    // buttons cant be edited
+                    Some(current) if current != text => {
+                        actions.push(Action::DeleteButton { value: value.clone() });
+                        actions.push(Action::AddButton { value: value.clone(), text: text.clone() });
+                    }
+                    Some(_) => {}
+                }
+            }
+            for value in live.web_loader.keys().filter(|v| !wanted.contains_key(*v)) {
+                actions.push(Action::DeleteButton { value: value.clone() });
+            }
+        }
+
+        actions.extend(self.whitelist.iter().map(|ip| Action::AddWhitelist { ip: ip.clone() }));
+        actions.extend(self.remove_whitelist.iter().map(|ip| Action::RemoveWhitelist { ip: ip.clone() }));
+        actions.extend(self.webhooks.iter().filter(|w| w.id.as_deref().unwrap_or_default().is_empty()).map(|w| Action::CreateWebhook(w.clone())));
+
+        Ok(Plan { actions })
+    }
+}
+
+/// live state of the managed sections
+#[derive(Default)]
+pub(crate) struct Live {
+    pub(crate) settings: Option<Settings>,
+    pub(crate) subscriptions: BTreeMap<String, u32>,
+    /// name -> (value, authed)
+    pub(crate) variables: BTreeMap<String, (String, bool)>,
+    pub(crate) chat: BTreeMap<String, u64>,
+    pub(crate) web_loader: BTreeMap<String, String>,
+}
+
+/// one api call of a plan
+#[derive(Debug)]
+pub enum Action {
+    UpdateSettings(Box<SettingsPatch>),
+    CreateSubscription { name: String, level: u32 },
+    EditSubscription { name: String, level: u32 },
+    DeleteSubscription { name: String },
+    CreateVariable { name: String, value: String, authed: bool },
+    EditVariable { name: String, value: String },
+    DeleteVariable { name: String },
+    CreateChannel { name: String, delay: u64 },
+    EditChannel { name: String, delay: u64 },
+    DeleteChannel { name: String },
+    AddButton { value: String, text: String },
+    DeleteButton { value: String },
+    AddWhitelist { ip: String },
+    RemoveWhitelist { ip: String },
+    CreateWebhook(WebhookConfig),
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::UpdateSettings(patch) => write!(f, "~ settings {}", patch.changed().join(", ")),
+            Action::CreateSubscription { name, level } => write!(f, "+ subscription {} (level {})", name, level),
+            Action::EditSubscription { name, level } => write!(f, "~ subscription {} (level {})", name, level),
+            Action::DeleteSubscription { name } => write!(f, "- subscription {}", name),
+            Action::CreateVariable { name, authed, .. } => write!(f, "+ variable {}{}", name, if *authed { " (authed)" } else { "" }),
+            Action::EditVariable { name, .. } => write!(f, "~ variable {}", name),
+            Action::DeleteVariable { name } => write!(f, "- variable {}", name),
+            Action::CreateChannel { name, delay } => write!(f, "+ chat channel {} (delay {}s)", name, delay),
+            Action::EditChannel { name, delay } => write!(f, "~ chat channel {} (delay {}s)", name, delay),
+            Action::DeleteChannel { name } => write!(f, "- chat channel {}", name),
+            Action::AddButton { value, text } => write!(f, "+ web loader button {} ({})", value, text),
+            Action::DeleteButton { value } => write!(f, "- web loader button {}", value),
+            Action::AddWhitelist { ip } => write!(f, "+ whitelist {}", ip),
+            Action::RemoveWhitelist { ip } => write!(f, "- whitelist {}", ip),
+            Action::CreateWebhook(webhook) => write!(f, "+ webhook {}", webhook.baseurl),
+        }
+    }
+}
+
+impl Action {
+    fn apply(&self, client: &SellerClient) -> Result<String, String> {
+        match self {
+            Action::UpdateSettings(patch) => client.settings().patch(patch),
+            Action::CreateSubscription { name, level } => client.subscriptions().create(name, &level.to_string()),
+            Action::EditSubscription { name, level } => client.subscriptions().edit(name, &level.to_string()),
+            Action::DeleteSubscription { name } => client.subscriptions().delete(name),
+            Action::CreateVariable { name, value, authed } => client.variables().create(name, value, *authed),
+            Action::EditVariable { name, value } => client.variables().edit(name, value),
+            Action::DeleteVariable { name } => client.variables().delete(name),
+            Action::CreateChannel { name, delay } => client.chat().create_channel(name, &delay.to_string()),
+            Action::EditChannel { name, delay } => client.chat().edit_channel(name, &delay.to_string()),
+            Action::DeleteChannel { name } => client.chat().delete_channel(name),
+            Action::AddButton { value, text } => client.web_loader().add_button(value, text),
+            Action::DeleteButton { value } => client.web_loader().delete_button(value),
+            Action::AddWhitelist { ip } => client.blacklists().add_whitelist(ip),
+            Action::RemoveWhitelist { ip } => client.blacklists().del_whitelist(ip),
+            Action::CreateWebhook(webhook) => client.webhook_create(&webhook.baseurl, &webhook.user_agent, Some(webhook.authed)),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Plan {
+    pub actions: Vec<Action>,
+}
+
+impl Plan {
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// runs every action in order, a failed action doesnt stop the others
+    pub fn apply(self, client: &SellerClient) -> ApplyReport {
+        let results = self.actions.into_iter().map(|action| {
+            let result = action.apply(client);
+            (action, result)
+        }).collect();
+        ApplyReport { results }
+    }
+}
+
+/// every action with keyauth's response, for CreateWebhook the response is the new id
+#[derive(Debug, Default)]
+pub struct ApplyReport {
+    pub results: Vec<(Action, Result<String, String>)>,
+}
+
+impl ApplyReport {
+    pub fn is_success(&self) -> bool {
+        self.results.iter().all(|(_, r)| r.is_ok())
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = (&Action, &String)> {
+        self.results.iter().filter_map(|(action, r)| r.as_ref().err().map(|e| (action, e)))
+    }
+}
+
+fn yaml_to_json(yaml: &yaml_rust2::Yaml) -> Result<Value, String> {
+    use yaml_rust2::Yaml;
+    Ok(match yaml {
+        Yaml::Null => Value::Null,
+        Yaml::Boolean(b) => Value::Bool(*b),
+        Yaml::Integer(i) => Value::from(*i),
+        Yaml::Real(s) => s.parse::<f64>().map(Value::from).map_err(|e| e.to_string())?,
+        Yaml::String(s) => Value::String(s.clone()),
+        Yaml::Array(items) => Value::Array(items.iter().map(yaml_to_json).collect::<Result<_, _>>()?),
+        Yaml::Hash(hash) => {
+            let mut map = serde_json::Map::new();
+            for (key, value) in hash {
+                let key = match key {
+                    Yaml::String(s) => s.clone(),
+                    Yaml::Integer(i) => i.to_string(),
+                    Yaml::Real(s) => s.clone(),
+                    Yaml::Boolean(b) => b.to_string(),
+                    _ => return Err("yaml keys have to be strings".to_string()),
+                };
+                map.insert(key, yaml_to_json(value)?);
+            }
+            Value::Object(map)
+        }
+        Yaml::Alias(_) | Yaml::BadValue => return Err("unsupported yaml value".to_string()),
+    })
+}
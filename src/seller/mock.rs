@@ -0,0 +1,420 @@
+/*!
+an in memory stand in for the seller api, for tests and for trying tools like the dashboard without a real app.
+it only implements the endpoints listed in [`MockApp::handle`], everything else answers with success false
+
+```rust,no_run
+use keyauth::seller::mock::{MockApp, MockServer};
+let server = MockServer::start(MockApp::demo()).unwrap();
+let client = server.client();
+client.users().ban("alice", Some("chargeback".to_string())).unwrap();
+assert_eq!(server.app().users["alice"].banned.as_deref(), Some("chargeback"));
+```
+*/
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde_json::{json, Value};
+use super::SellerClient;
+use super::settings::Settings;
+
+/// the seller key the mock accepts unless MockApp::seller_key is changed
+pub const MOCK_SELLER_KEY: &str = "mock-seller-key";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MockUser {
+    pub hwid: Option<String>,
+    pub ip: Option<String>,
+    /// ban reason
+    pub banned: Option<String>,
+    pub createdate: u64,
+    pub lastlogin: Option<u64>,
+    /// subscription name -> expiry as unix seconds
+    pub subscriptions: BTreeMap<String, u64>,
+    pub vars: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MockSession {
+    pub id: String,
+    pub credential: Option<String>,
+    pub expiry: u64,
+    pub validated: bool,
+    pub ip: String,
+}
+
+/// state of the mocked application, change it directly to set up a test
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockApp {
+    pub seller_key: String,
+    pub name: String,
+    pub users: BTreeMap<String, MockUser>,
+    pub sessions: Vec<MockSession>,
+    pub settings: Settings,
+    /// the next this many requests are answered with 429 too many requests
+    pub throttle: u32,
+    /// every request the server got, as query parameters
+    pub requests: Vec<BTreeMap<String, String>>,
+}
+
+impl Default for MockApp {
+    fn default() -> Self {
+        Self {
+            seller_key: MOCK_SELLER_KEY.to_string(),
+            name: "mock".to_string(),
+            users: BTreeMap::new(),
+            sessions: Vec::new(),
+            settings: Settings { enabled: true, version: "1.0".to_string(), cooldown: "120".to_string(), ..Default::default() },
+            throttle: 0,
+            requests: Vec::new(),
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+impl MockApp {
+    /// a few users and sessions to click through
+    pub fn demo() -> Self {
+        let now = now();
+        let mut app = MockApp::default();
+        let user = |hwid: Option<&str>, banned: Option<&str>, sub: &str, days: i64| MockUser {
+            hwid: hwid.map(str::to_string),
+            ip: Some("203.0.113.7".to_string()),
+            banned: banned.map(str::to_string),
+            createdate: now - 30 * 86400,
+            lastlogin: Some(now - 3600),
+            subscriptions: BTreeMap::from([(sub.to_string(), (now as i64 + days * 86400) as u64)]),
+            vars: BTreeMap::new(),
+        };
+        app.users.insert("alice".to_string(), user(Some("HWID-ALICE"), None, "default", 30));
+        app.users.insert("bob".to_string(), user(None, None, "vip", -2));
+        app.users.insert("mallory".to_string(), user(Some("HWID-MALLORY"), Some("sharing accounts"), "default", 10));
+        app.users.get_mut("alice").unwrap().vars.insert("theme".to_string(), "dark".to_string());
+        app.sessions.push(MockSession { id: "s1".to_string(), credential: Some("alice".to_string()), expiry: now + 3600, validated: true, ip: "203.0.113.7".to_string() });
+        app.sessions.push(MockSession { id: "s2".to_string(), credential: None, expiry: now + 600, validated: false, ip: "198.51.100.3".to_string() });
+        app
+    }
+
+    /// answers one seller api request
+    pub fn handle(&mut self, params: &BTreeMap<String, String>) -> Value {
+        self.requests.push(params.clone());
+        if params.get("sellerkey") != Some(&self.seller_key) {
+            return fail("Seller key not found");
+        }
+        let get = |key: &str| params.get(key).map(String::as_str).unwrap_or_default();
+        let user = get("user");
+        match get("type") {
+            "fetchallusers" => {
+                let users: Vec<Value> = self.users.iter().enumerate().map(|(i, (name, u))| json!({
+                    "id": (i + 1).to_string(),
+                    "username": name,
+                    "email": null,
+                    "password": null,
+                    "hwid": u.hwid,
+                    "app": self.name,
+                    "owner": "mock",
+                    "createdate": u.createdate.to_string(),
+                    "lastlogin": u.lastlogin.map(|l| l.to_string()),
+                    "banned": u.banned,
+                    "ip": u.ip,
+                    "cooldown": null,
+                })).collect();
+                ok_with("users", users)
+            }
+            "fetchallusernames" => ok_with("usernames", self.users.keys().map(|n| json!({ "username": n })).collect()),
+            "fetchalluservars" => {
+                let vars: Vec<Value> = self.users.iter()
+                    .flat_map(|(name, u)| u.vars.iter().map(move |(var, data)| json!({ "name": var, "data": data, "user": name })))
+                    .collect();
+                ok_with("vars", vars)
+            }
+            "userdata" => match self.users.get(user) {
+                Some(u) => json!({
+                    "success": true,
+                    "message": "Successfully retrieved user data",
+                    "username": user,
+                    "subscriptions": u.subscriptions.iter().map(|(sub, expiry)| json!({ "subscription": sub, "expiry": expiry.to_string(), "key": null })).collect::<Vec<_>>(),
+                    "ip": u.ip,
+                    "hwid": u.hwid,
+                    "createdate": u.createdate.to_string(),
+                    "lastlogin": u.lastlogin.map(|l| l.to_string()),
+                    "cooldown": null,
+                    "token": format!("token-{}", user),
+                }),
+                None => fail("User not found"),
+            },
+            "countsubs" => {
+                let count = self.users.values().filter(|u| u.subscriptions.contains_key(get("name"))).count();
+                json!({ "success": true, "message": "Successfully counted subscriptions", "count": count.to_string() })
+            }
+            "adduser" => {
+                if self.users.contains_key(user) {
+                    return fail("Username already exists");
+                }
+                let days: u64 = get("expiry").parse().unwrap_or(1);
+                self.users.insert(user.to_string(), MockUser {
+                    createdate: now(),
+                    subscriptions: BTreeMap::from([(get("sub").to_string(), now() + days * 86400)]),
+                    ..Default::default()
+                });
+                ok("Successfully created user")
+            }
+            "deluser" => match self.users.remove(user) {
+                Some(_) => ok("Successfully deleted user"),
+                None => fail("User not found"),
+            },
+            "banuser" => self.with_user(user, |u| u.banned = Some(params.get("reason").cloned().unwrap_or_default()), "Successfully banned user"),
+            "unbanuser" => self.with_user(user, |u| u.banned = None, "Successfully unbanned user"),
+            "resetuser" => self.with_user(user, |u| u.hwid = None, "Successfully reset user"),
+            "addhwiduser" => self.with_user(user, |u| u.hwid = Some(params.get("hwid").cloned().unwrap_or_default()), "Successfully added HWID"),
+            "setvar" => self.with_user(user, |u| { u.vars.insert(get("var").to_string(), get("data").to_string()); }, "Successfully set variable"),
+            "getvar" => match self.users.get(user).and_then(|u| u.vars.get(get("var"))) {
+                Some(data) => json!({ "success": true, "message": "Successfully retrieved variable", "response": data }),
+                None => fail("Variable not found for user"),
+            },
+            "extend" => {
+                let Some(u) = self.users.get_mut(user) else {
+                    return fail("User not found");
+                };
+                let days: u64 = match get("expiry").parse() {
+                    Ok(days) => days,
+                    Err(_) => return fail("Invalid expiry"),
+                };
+                let now = now();
+                let expiry = u.subscriptions.entry(get("sub").to_string()).or_insert(now);
+                if *expiry <= now {
+                    if get("active_only") == "1" {
+                        return fail("Subscription is not active");
+                    }
+                    *expiry = now;
+                }
+                *expiry += days * 86400;
+                ok("Successfully extended user")
+            }
+            "fetchallsessions" => {
+                let sessions: Vec<Value> = self.sessions.iter().map(|s| json!({
+                    "id": s.id,
+                    "credential": s.credential,
+                    "expiry": s.expiry.to_string(),
+                    "validated": if s.validated { "1" } else { "0" },
+                    "ip": s.ip,
+                })).collect();
+                ok_with("sessions", sessions)
+            }
+            "kill" => {
+                let before = self.sessions.len();
+                self.sessions.retain(|s| s.id != get("session"));
+                if self.sessions.len() == before {
+                    return fail("Session not found");
+                }
+                ok("Successfully killed session")
+            }
+            "killall" => {
+                self.sessions.clear();
+                ok("Successfully killed all sessions")
+            }
+            "getsettings" => {
+                let mut value = serde_json::to_value(&self.settings).unwrap_or_default();
+                value["success"] = json!(true);
+                value["message"] = json!("Successfully retrieved settings");
+                value
+            }
+            "updatesettings" => {
+                let mut settings = self.settings.clone();
+                for (param, value) in params {
+                    let flag = || value == "1" || value == "true";
+                    match param.as_str() {
+                        "sellerkey" | "type" => {}
+                        "enabled" => settings.enabled = flag(),
+                        "hwidcheck" => settings.hwidcheck = flag(),
+                        "ver" => settings.version = value.clone(),
+                        "download" => settings.webdownload = value.clone(),
+                        "webhook" => settings.webhook = value.clone(),
+                        "resellerstore" => settings.resellerstore = value.clone(),
+                        "appdisabled" => settings.disabledmsg = value.clone(),
+                        "usernametaken" => settings.usernametakenmsg = value.clone(),
+                        "keynotfound" => settings.licenseinvalidmsg = value.clone(),
+                        "keyused" => settings.keytakenmsg = value.clone(),
+                        "nosublevel" => settings.nosubmsg = value.clone(),
+                        "usernamenotfound" => settings.userinvalidmsg = value.clone(),
+                        "passmismatch" => settings.passinvalidmsg = value.clone(),
+                        "hwidmismatch" => settings.hwidmismatchmsg = value.clone(),
+                        "noactivesubs" => settings.noactivesubmsg = value.clone(),
+                        "hwidblacked" => settings.blackedmsg = value.clone(),
+                        "keypaused" => settings.pausedmsg = value.clone(),
+                        "keyexpired" => settings.expiredmsg = value.clone(),
+                        "sellixsecret" => settings.sellixsecret = value.clone(),
+                        "dayproduct" => settings.dayresellerproductid = value.clone(),
+                        "weekproduct" => settings.weekresellerproductid = value.clone(),
+                        "monthproduct" => settings.monthresellerproductid = value.clone(),
+                        "lifetimeproduct" => settings.liferesellerproductid = value.clone(),
+                        other => return fail(&format!("Unknown setting {}", other)),
+                    }
+                }
+                self.settings = settings;
+                ok("Successfully updated settings")
+            }
+            other => fail(&format!("mock server doesnt implement type {}", other)),
+        }
+    }
+
+    fn with_user(&mut self, name: &str, change: impl FnOnce(&mut MockUser), message: &str) -> Value {
+        match self.users.get_mut(name) {
+            Some(user) => {
+                change(user);
+                ok(message)
+            }
+            None => fail("User not found"),
+        }
+    }
+}
+
+fn ok(message: &str) -> Value {
+    json!({ "success": true, "message": message })
+}
+
+/// keyauth answers an empty list with success false and a message
+fn ok_with(key: &str, items: Vec<Value>) -> Value {
+    if items.is_empty() {
+        return fail(&format!("No {} found", key));
+    }
+    let mut value = ok(&format!("Successfully retrieved {}", key));
+    value[key] = Value::Array(items);
+    value
+}
+
+fn fail(message: &str) -> Value {
+    json!({ "success": false, "message": message })
+}
+
+/// http server on localhost that answers with a [`MockApp`], stops when dropped
+pub struct MockServer {
+    url: String,
+    app: Arc<Mutex<MockApp>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// listens on a free port of 127.0.0.1
+    pub fn start(app: MockApp) -> Result<MockServer, String> {
+        MockServer::bind("127.0.0.1:0", app)
+    }
+
+    pub fn bind(addr: &str, app: MockApp) -> Result<MockServer, String> {
+        let listener = TcpListener::bind(addr).map_err(|e| e.to_string())?;
+        let local = listener.local_addr().map_err(|e| e.to_string())?;
+        let app = Arc::new(Mutex::new(app));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let (app, stop) = (app.clone(), stop.clone());
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if stop.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        let _ = serve(stream, &app);
+                    }
+                }
+            })
+        };
+        Ok(MockServer { url: format!("http://{}/", local), app, stop, thread: Some(thread) })
+    }
+
+    /// base url to give to SellerClient
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// a client with the right seller key
+    pub fn client(&self) -> SellerClient {
+        let key = self.app().seller_key.clone();
+        SellerClient::new(&key, self.url.clone())
+    }
+
+    /// the state of the app, to set up or check a test
+    pub fn app(&self) -> MutexGuard<'_, MockApp> {
+        self.app.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// blocks until the process is killed, for running the mock by itself
+    pub fn wait(mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // wake up the accept loop so it sees the flag
+        let _ = TcpStream::connect(self.url.trim_start_matches("http://").trim_end_matches('/'));
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn serve(stream: TcpStream, app: &Mutex<MockApp>) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // skip the headers, the seller api only uses the query string
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+    let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let params = target.split_once('?').map(|(_, q)| parse_query(q)).unwrap_or_default();
+    let mut stream = stream;
+    let mut app = app.lock().unwrap_or_else(|e| e.into_inner());
+    if app.throttle > 0 {
+        app.throttle -= 1;
+        let body = fail("Too many requests").to_string();
+        write!(stream, "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body)?;
+        return stream.flush();
+    }
+    let body = app.handle(&params).to_string();
+    drop(app);
+    write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body)?;
+    stream.flush()
+}
+
+fn parse_query(query: &str) -> BTreeMap<String, String> {
+    query.split('&').filter(|p| !p.is_empty()).map(|pair| {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        (decode(key), decode(value))
+    }).collect()
+}
+
+fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                match std::str::from_utf8(&bytes[i + 1..i + 3]).ok().and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(b) => {
+                        out.push(b);
+                        i += 2;
+                    }
+                    None => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
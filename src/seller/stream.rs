@@ -0,0 +1,242 @@
+/*!
+incremental parsing of the big lists the seller api returns, only one item is in memory at a time.
+keyauth has no pagination, so the stream_* functions read the array out of the http body while it arrives
+
+```rust,no_run
+use keyauth::seller::SellerClient;
+let client = SellerClient::new("sellerkey", "https://keyauth.win/api/seller/".to_string());
+let mut unused = 0;
+for license in client.licenses().stream_all() {
+    if license.unwrap().usedby.is_none() {
+        unused += 1;
+    }
+}
+println!("{} unused licenses", unused);
+```
+*/
+
+use std::io::{BufRead, BufReader, Read};
+use std::marker::PhantomData;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+enum State {
+    /// before the opening brace of the response
+    Start,
+    /// inside the response object, looking for the key of the array
+    Object,
+    /// inside the array, before an item or the closing bracket
+    Array { first: bool },
+    Done,
+}
+
+/// iterator over the items of one array in a seller api response, e.g. `keys` of fetchallkeys.
+/// if keyauth answers with success false the iterator yields the message as the only Err,
+/// a string instead of the array (keyauth's way of saying the list is empty) yields nothing
+pub struct JsonStream<T> {
+    reader: BufReader<Box<dyn Read + Send>>,
+    key: String,
+    state: State,
+    success: Option<bool>,
+    message: Option<String>,
+    item: Vec<u8>,
+    /// the request failed before there was a response
+    error: Option<String>,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> JsonStream<T> {
+    /// key is the name of the array in the response object
+    pub fn new<R: Read + Send + 'static>(reader: R, key: &str) -> Self {
+        Self {
+            reader: BufReader::new(Box::new(reader)),
+            key: key.to_string(),
+            state: State::Start,
+            success: None,
+            message: None,
+            item: Vec::new(),
+            error: None,
+            _item: PhantomData,
+        }
+    }
+
+    /// a stream that only yields error, for a request that got no response
+    pub fn failed(error: String) -> Self {
+        let mut stream = Self::new(std::io::empty(), "");
+        stream.state = State::Done;
+        stream.error = Some(error);
+        stream
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, String> {
+        let buf = self.reader.fill_buf().map_err(|e| e.to_string())?;
+        Ok(buf.first().copied())
+    }
+
+    fn bump(&mut self) -> Result<u8, String> {
+        let b = self.peek()?.ok_or("response ended early")?;
+        self.reader.consume(1);
+        Ok(b)
+    }
+
+    /// next byte that isnt whitespace, without consuming it
+    fn peek_token(&mut self) -> Result<Option<u8>, String> {
+        while let Some(b) = self.peek()? {
+            if !b.is_ascii_whitespace() {
+                return Ok(Some(b));
+            }
+            self.reader.consume(1);
+        }
+        Ok(None)
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), String> {
+        match self.peek_token()? {
+            Some(b) if b == expected => {
+                self.reader.consume(1);
+                Ok(())
+            }
+            Some(b) => Err(format!("expected {:?} in response, found {:?}", expected as char, b as char)),
+            None => Err("response ended early".to_string()),
+        }
+    }
+
+    /// reads one complete json value into self.item
+    fn read_value(&mut self) -> Result<(), String> {
+        self.item.clear();
+        self.peek_token()?;
+        let mut depth = 0usize;
+        loop {
+            let b = match self.peek()? {
+                Some(b) => b,
+                None if depth == 0 && !self.item.is_empty() => return Ok(()),
+                None => return Err("response ended early".to_string()),
+            };
+            if depth == 0 && !self.item.is_empty() && matches!(b, b',' | b']' | b'}' | b' ' | b'\t' | b'\r' | b'\n') {
+                return Ok(());
+            }
+            self.reader.consume(1);
+            self.item.push(b);
+            match b {
+                b'"' => self.read_string_rest()?,
+                b'{' | b'[' => depth += 1,
+                b'}' | b']' => {
+                    depth = depth.checked_sub(1).ok_or("unbalanced json in response")?;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+            if depth == 0 && b == b'"' {
+                return Ok(());
+            }
+        }
+    }
+
+    /// copies the rest of a string after its opening quote into self.item
+    fn read_string_rest(&mut self) -> Result<(), String> {
+        loop {
+            let b = self.bump()?;
+            self.item.push(b);
+            match b {
+                b'\\' => {
+                    let escaped = self.bump()?;
+                    self.item.push(escaped);
+                }
+                b'"' => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+
+    /// the error for a response without the array
+    fn missing(&self) -> String {
+        match (self.success, &self.message) {
+            (Some(false), Some(message)) => message.clone(),
+            _ => format!("response has no {}", self.key),
+        }
+    }
+
+    fn advance(&mut self) -> Result<Option<T>, String> {
+        loop {
+            match self.state {
+                State::Start => {
+                    self.expect(b'{')?;
+                    self.state = State::Object;
+                    if self.peek_token()? == Some(b'}') {
+                        self.state = State::Done;
+                        return Err(self.missing());
+                    }
+                }
+                State::Object => {
+                    self.read_value()?;
+                    let key: String = serde_json::from_slice(&self.item).map_err(|e| e.to_string())?;
+                    self.expect(b':')?;
+                    if key == self.key && self.peek_token()? == Some(b'[') {
+                        self.reader.consume(1);
+                        self.state = State::Array { first: true };
+                        continue;
+                    }
+                    self.read_value()?;
+                    let value: Value = serde_json::from_slice(&self.item).map_err(|e| e.to_string())?;
+                    if key == self.key {
+                        // a string instead of the array means there is nothing
+                        self.state = State::Done;
+                        return match value {
+                            Value::String(_) | Value::Null => Ok(None),
+                            _ => Err(format!("{} in the response isnt a list", self.key)),
+                        };
+                    }
+                    match key.as_str() {
+                        "success" => self.success = value.as_bool(),
+                        "message" => self.message = Some(value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string())),
+                        _ => {}
+                    }
+                    match self.peek_token()? {
+                        Some(b',') => self.reader.consume(1),
+                        Some(b'}') => {
+                            self.state = State::Done;
+                            return Err(self.missing());
+                        }
+                        _ => return Err("invalid json in response".to_string()),
+                    }
+                }
+                State::Array { first } => {
+                    match self.peek_token()? {
+                        Some(b']') => {
+                            self.reader.consume(1);
+                            self.state = State::Done;
+                            return Ok(None);
+                        }
+                        Some(b',') if !first => self.reader.consume(1),
+                        _ if first => {}
+                        _ => return Err("invalid json in response".to_string()),
+                    }
+                    self.read_value()?;
+                    self.state = State::Array { first: false };
+                    return serde_json::from_slice(&self.item).map(Some).map_err(|e| e.to_string());
+                }
+                State::Done => return Ok(None),
+            }
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for JsonStream<T> {
+    type Item = Result<T, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.error.take() {
+            return Some(Err(err));
+        }
+        match self.advance() {
+            Ok(item) => item.map(Ok),
+            Err(err) => {
+                // the position in the body is lost after an error
+                self.state = State::Done;
+                Some(Err(err))
+            }
+        }
+    }
+}
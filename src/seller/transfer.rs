@@ -0,0 +1,246 @@
+/*!
+export licenses and users to csv or json lines, and create them in bulk from a csv
+
+```rust,no_run
+use keyauth::seller::{SellerClient, transfer::{self, Format}};
+let client = SellerClient::new("sellerkey", "https://keyauth.win/api/seller/".to_string());
+transfer::export_licenses(&client, Format::Csv, std::fs::File::create("licenses.csv").unwrap()).unwrap();
+
+// check the file first, dry_run only validates the rows
+let report = transfer::import_licenses(&client, std::fs::File::open("new-licenses.csv").unwrap(), true).unwrap();
+if report.failed() == 0 {
+    transfer::import_licenses(&client, std::fs::File::open("new-licenses.csv").unwrap(), false).unwrap();
+}
+```
+*/
+
+use std::io::{Read, Write};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use super::{is_none_found, SellerClient};
+use super::licenses::{Charset, LicenseSpec};
+
+/// output format of the export functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// with a header row
+    Csv,
+    /// one json object per line
+    JsonLines,
+}
+
+/// writes every license to writer while the list downloads, returns how many were written (0 for an app without licenses)
+pub fn export_licenses<W: Write>(client: &SellerClient, format: Format, writer: W) -> Result<usize, String> {
+    write_rows(client.licenses().stream_all(), format, writer)
+}
+
+/// writes every user to writer while the list downloads, returns how many were written (0 for an app without users)
+pub fn export_users<W: Write>(client: &SellerClient, format: Format, writer: W) -> Result<usize, String> {
+    write_rows(client.users().stream_all_users(), format, writer)
+}
+
+/// on an error the rows before it are already written, keyauth's answer for an empty list writes nothing
+fn write_rows<T, I, W>(rows: I, format: Format, mut writer: W) -> Result<usize, String>
+where
+    T: Serialize,
+    I: IntoIterator<Item = Result<T, String>>,
+    W: Write,
+{
+    let mut rows = rows.into_iter().peekable();
+    if let Some(Err(err)) = rows.peek() {
+        if is_none_found(err) {
+            return Ok(0);
+        }
+    }
+    let mut written = 0;
+    match format {
+        Format::Csv => {
+            let mut csv = csv::Writer::from_writer(writer);
+            for row in rows {
+                csv.serialize(row?).map_err(|e| e.to_string())?;
+                written += 1;
+            }
+            csv.flush().map_err(|e| e.to_string())?;
+        }
+        Format::JsonLines => {
+            for row in rows {
+                serde_json::to_writer(&mut writer, &row?).map_err(|e| e.to_string())?;
+                writer.write_all(b"\n").map_err(|e| e.to_string())?;
+                written += 1;
+            }
+            writer.flush().map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(written)
+}
+
+/// a row of a license import csv, only expiry_days is required
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LicenseRow {
+    pub expiry_days: f64,
+    #[serde(default)]
+    pub mask: Option<String>,
+    #[serde(default)]
+    pub level: Option<u32>,
+    #[serde(default)]
+    pub amount: Option<u32>,
+    #[serde(default)]
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// random, uppercase or lowercase
+    #[serde(default)]
+    pub charset: Option<String>,
+}
+
+impl LicenseRow {
+    pub fn to_spec(&self) -> Result<LicenseSpec, String> {
+        if !self.expiry_days.is_finite() || self.expiry_days <= 0.0 {
+            return Err("expiry_days has to be positive".to_string());
+        }
+        let mut spec = LicenseSpec::new(Duration::from_secs((self.expiry_days * 86400.0).round() as u64));
+        if let Some(mask) = &self.mask {
+            spec = spec.mask(mask.as_str());
+        }
+        if let Some(level) = self.level {
+            spec = spec.level(level);
+        }
+        if let Some(amount) = self.amount {
+            spec = spec.amount(amount);
+        }
+        spec.owner = self.owner.clone();
+        spec.note = self.note.clone();
+        spec.charset = match self.charset.as_deref().map(str::to_ascii_lowercase).as_deref() {
+            None | Some("") | Some("random") => Charset::Random,
+            Some("uppercase") => Charset::Uppercase,
+            Some("lowercase") => Charset::Lowercase,
+            Some(other) => return Err(format!("unknown charset {:?}", other)),
+        };
+        spec.validate()?;
+        Ok(spec)
+    }
+}
+
+/// a row of a user import csv, a user without password has to set it on first login
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct UserRow {
+    pub username: String,
+    pub subscription: String,
+    pub expiry_days: u64,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+impl UserRow {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.username.is_empty() {
+            return Err("username is empty".to_string());
+        }
+        if self.subscription.is_empty() {
+            return Err("subscription is empty".to_string());
+        }
+        if self.expiry_days == 0 {
+            return Err("expiry_days has to be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RowStatus {
+    /// dry run and the row is valid
+    Valid,
+    Created,
+    /// only some of the keys of a license row were created
+    Partial,
+    Failed,
+}
+
+/// what happened to one row of an import
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RowReport {
+    /// line in the csv, the header is line 1
+    pub line: u64,
+    pub status: RowStatus,
+    /// keyauth's response or the reason the row failed
+    pub message: String,
+    /// created license keys, empty for users
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub rows: Vec<RowReport>,
+}
+
+impl ImportReport {
+    pub fn succeeded(&self) -> usize {
+        self.rows.iter().filter(|r| matches!(r.status, RowStatus::Valid | RowStatus::Created)).count()
+    }
+
+    /// rows that failed completely or partially
+    pub fn failed(&self) -> usize {
+        self.rows.len() - self.succeeded()
+    }
+
+    /// writes the report as csv, keys are joined with spaces
+    pub fn write_csv<W: Write>(&self, writer: W) -> Result<(), String> {
+        let mut csv = csv::Writer::from_writer(writer);
+        csv.write_record(["line", "status", "message", "keys"]).map_err(|e| e.to_string())?;
+        for row in &self.rows {
+            let status = format!("{:?}", row.status);
+            csv.write_record([row.line.to_string().as_str(), &status, &row.message, &row.keys.join(" ")]).map_err(|e| e.to_string())?;
+        }
+        csv.flush().map_err(|e| e.to_string())
+    }
+}
+
+/// creates the licenses of every row in reader, a failed row doesnt stop the import.
+/// Err only if the csv itself cant be read
+pub fn import_licenses<R: Read>(client: &SellerClient, reader: R, dry_run: bool) -> Result<ImportReport, String> {
+    import(reader, |row: LicenseRow| {
+        let spec = row.to_spec()?;
+        if dry_run {
+            return Ok((RowStatus::Valid, format!("{} keys", spec.amount), Vec::new()));
+        }
+        let created = client.licenses().create_bulk(&spec)?;
+        if created.is_complete() {
+            return Ok((RowStatus::Created, format!("{} keys", created.keys.len()), created.keys));
+        }
+        let errors: Vec<&str> = created.failures.iter().map(|f| f.error.as_str()).collect();
+        Ok((RowStatus::Partial, format!("{} keys not created: {}", created.failed(), errors.join(", ")), created.keys))
+    })
+}
+
+/// creates the users of every row in reader, a failed row doesnt stop the import.
+/// Err only if the csv itself cant be read
+pub fn import_users<R: Read>(client: &SellerClient, reader: R, dry_run: bool) -> Result<ImportReport, String> {
+    import(reader, |row: UserRow| {
+        row.validate()?;
+        if dry_run {
+            return Ok((RowStatus::Valid, String::new(), Vec::new()));
+        }
+        let message = client.users().create(&row.username, &row.subscription, row.expiry_days, row.password)?;
+        Ok((RowStatus::Created, message, Vec::new()))
+    })
+}
+
+type RowResult = Result<(RowStatus, String, Vec<String>), String>;
+
+fn import<R: Read, T: for<'de> Deserialize<'de>>(reader: R, mut create: impl FnMut(T) -> RowResult) -> Result<ImportReport, String> {
+    let mut csv = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(reader);
+    let headers = csv.headers().map_err(|e| e.to_string())?.clone();
+    let mut report = ImportReport::default();
+    for record in csv.records() {
+        let record = record.map_err(|e| e.to_string())?;
+        let line = record.position().map_or(0, |p| p.line());
+        let result = record.deserialize(Some(&headers))
+            .map_err(|e| e.to_string())
+            .and_then(&mut create);
+        report.rows.push(match result {
+            Ok((status, message, keys)) => RowReport { line, status, message, keys },
+            Err(message) => RowReport { line, status: RowStatus::Failed, message, keys: Vec::new() },
+        });
+    }
+    Ok(report)
+}
@@ -12,3 +12,840 @@ fn bench1() {
     keyauth.init(None);
     keyauth.login("demoseller".to_string(), "R9yzxdRyybgY75".to_string(), None);
 }
+
+#[test]
+fn chat_message_timestamps() {
+    use std::time::{Duration, UNIX_EPOCH};
+    let messages: Vec<crate::ChatMessage> = serde_json::from_str(r#"[{"author":"a","message":"hi","timestamp":"1640995200"},{"author":"b","message":"yo","timestamp":1640995260}]"#).unwrap();
+    assert_eq!(messages[0].timestamp, UNIX_EPOCH + Duration::from_secs(1640995200));
+    assert_eq!(messages[1].timestamp, UNIX_EPOCH + Duration::from_secs(1640995260));
+    assert_eq!(messages[1].author, "b");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn memfile_from_bytes() {
+    let mut bytes = b"#!/bin/sh\necho hi\n".to_vec();
+    let mem = crate::v1_2::MemFile::from_bytes("module", &mut bytes, true).unwrap();
+    assert!(bytes.iter().all(|&b| b == 0));
+    assert_eq!(std::fs::read(mem.path()).unwrap(), b"#!/bin/sh\necho hi\n");
+}
+
+#[test]
+fn file_hash_is_md5() {
+    let path = std::env::temp_dir().join("keyauth-file-hash-test");
+    std::fs::write(&path, b"keyauth").unwrap();
+    assert_eq!(crate::file_hash(&path).unwrap(), "55ae3167667ec809b9ae5a63d56c2168");
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(crate::self_hash().unwrap().len(), 32);
+}
+
+#[test]
+#[cfg(all(feature = "updater", unix))]
+fn updater_replaces_and_restarts() {
+    use std::path::PathBuf;
+    use std::process::Command;
+    if let Some(dir) = std::env::var_os("KEYAUTH_UPDATER_DIR") {
+        // running as the copied binary, swap in a script and restart into it
+        let dir = PathBuf::from(dir);
+        let new = dir.join("app.new");
+        std::fs::write(&new, format!("#!/bin/sh\necho restarted > '{}'\n", dir.join("marker").display())).unwrap();
+        panic!("restart failed: {}", crate::updater::replace_and_restart(&new).unwrap_err());
+    }
+    let dir = std::env::temp_dir().join(format!("keyauth-updater-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy(std::env::current_exe().unwrap(), dir.join("app")).unwrap();
+    let status = Command::new(dir.join("app"))
+        .args(["--exact", "test::updater_replaces_and_restarts"])
+        .env("KEYAUTH_UPDATER_DIR", &dir)
+        .status()
+        .unwrap();
+    let marker = std::fs::read_to_string(dir.join("marker"));
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(status.success());
+    assert_eq!(marker.unwrap(), "restarted\n");
+}
+
+#[test]
+#[cfg(all(feature = "updater", feature = "v1_2"))]
+fn updater_init_hook() {
+    use std::io::{Read, Write};
+    // answers once with invalidver, signed with the app secret
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let _ = stream.read(&mut [0u8; 4096]);
+        let body = r#"{"success":false,"message":"invalidver","download":"https://example.com/app"}"#;
+        let signature = hex::encode(hmac_sha256::HMAC::mac(body, "secret"));
+        write!(stream, "HTTP/1.1 200 OK\r\nsignature: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", signature, body.len(), body).unwrap();
+    });
+    let mut auth = crate::v1_2::KeyauthApi::new("app", "owner", "secret", "1.0", &url);
+    let mut seen = Vec::new();
+    let result = crate::updater::init_with_hook(&mut auth, None, &mut |update: &crate::UpdateRequired| {
+        seen.push(update.download_url.clone());
+        Err("no update today".to_string())
+    });
+    server.join().unwrap();
+    assert_eq!(result, Err(crate::InitError::Other("no update today".to_string())));
+    assert_eq!(seen, vec!["https://example.com/app".to_string()]);
+}
+
+#[test]
+#[cfg(feature = "seller")]
+fn license_spec_validation() {
+    use std::time::Duration;
+    use crate::seller::licenses::{LicenseSpec, validate_mask};
+    assert!(validate_mask("PRO-****-XXXX").is_ok());
+    assert!(validate_mask("PRO-KEY").is_err());
+    assert!(validate_mask("PRO_****").is_err());
+    let spec = LicenseSpec::new(Duration::from_secs(86400 * 30)).amount(2500);
+    assert!(spec.validate().is_ok());
+    assert_eq!(spec.expiry_days(), "30");
+    assert_eq!(LicenseSpec::new(Duration::from_secs(43200)).expiry_days(), "0.5");
+    assert!(LicenseSpec::new(Duration::ZERO).validate().is_err());
+}
+
+#[test]
+#[cfg(feature = "seller")]
+fn legacy_license_create_rejects_bad_input() {
+    use crate::seller::SellerClient;
+    // checked before anything is sent
+    let client = SellerClient::new("sellerkey", "http://127.0.0.1:9/".to_string());
+    assert_eq!(client.licenses().create(30, None, Some(-1), None, None), Err("invalid level -1".to_string()));
+    assert_eq!(client.licenses().create(u64::MAX, None, None, None, None), Err(format!("expiry of {} days is too long", u64::MAX)));
+}
+
+#[test]
+#[cfg(feature = "seller")]
+fn license_parsing_and_filters() {
+    use std::time::{Duration, UNIX_EPOCH};
+    use crate::seller::licenses::{License, LicenseFilter, LicenseStatus};
+    let licenses: Vec<License> = serde_json::from_str(r#"[
+        {"id":"1","key":"AAA","note":null,"expires":"86400","status":"Not Used","level":"1","genby":"seller","gendate":"1640995200","usedon":null,"usedby":null,"app":"app","banned":null},
+        {"id":"2","key":"BBB","note":"","expires":86400,"status":"Used","level":2,"genby":"seller","gendate":1640995200,"usedon":"1640995200","usedby":"bob","app":"app","banned":null},
+        {"id":"3","key":"CCC","note":"vip","expires":"86400","status":"Banned","level":"1","genby":"seller","gendate":"1640995200","usedon":null,"usedby":null,"app":"app","banned":"chargeback"}
+    ]"#).unwrap();
+    assert_eq!(licenses[1].level, 2);
+    assert_eq!(licenses[0].expires, Duration::from_secs(86400));
+    assert_eq!(licenses[1].expires_at(), Some(UNIX_EPOCH + Duration::from_secs(1640995200 + 86400)));
+    assert_eq!(licenses[1].note, None);
+    assert_eq!(licenses[2].banned.as_deref(), Some("chargeback"));
+    assert_eq!(licenses[2].status, LicenseStatus::Banned);
+    assert_eq!(licenses.unused().len(), 1);
+    assert_eq!(licenses.used_by("bob")[0].key, "BBB");
+    assert_eq!(licenses.expired().len(), 1);
+}
+
+#[test]
+#[cfg(feature = "seller")]
+fn license_import_dry_run() {
+    use crate::seller::{SellerClient, transfer::{import_licenses, RowStatus}};
+    let client = SellerClient::new("sellerkey", "http://127.0.0.1:9/".to_string());
+    let csv = "expiry_days,mask,level,amount,owner,note,charset\n30,PRO-****,1,250,,giveaway,uppercase\n0.5,,,,,,\n7,NO_RANDOM,,,,,\n";
+    let report = import_licenses(&client, csv.as_bytes(), true).unwrap();
+    let statuses: Vec<_> = report.rows.iter().map(|r| (r.line, r.status)).collect();
+    assert_eq!(statuses, vec![(2, RowStatus::Valid), (3, RowStatus::Valid), (4, RowStatus::Failed)]);
+    assert_eq!(report.failed(), 1);
+}
+
+#[test]
+#[cfg(feature = "mock")]
+fn user_export_streams_rows() {
+    use crate::seller::mock::{MockApp, MockServer};
+    use crate::seller::transfer::{export_users, Format};
+    let server = MockServer::start(MockApp::demo()).unwrap();
+    let mut lines = Vec::new();
+    assert_eq!(export_users(&server.client(), Format::JsonLines, &mut lines), Ok(3));
+    let users: Vec<crate::seller::user::User> = String::from_utf8(lines).unwrap().lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(users.iter().map(|u| u.username.as_str()).collect::<Vec<_>>(), ["alice", "bob", "mallory"]);
+    let mut csv = Vec::new();
+    assert_eq!(export_users(&server.client(), Format::Csv, &mut csv), Ok(3));
+    assert_eq!(String::from_utf8(csv).unwrap().lines().count(), 4);
+    server.app().users.clear();
+    let mut empty = Vec::new();
+    assert_eq!(export_users(&server.client(), Format::Csv, &mut empty), Ok(0));
+    assert!(empty.is_empty());
+    let wrong_key = crate::seller::SellerClient::new("wrong", server.url().to_string());
+    assert_eq!(export_users(&wrong_key, Format::JsonLines, Vec::new()), Err("Seller key not found".to_string()));
+}
+
+#[test]
+#[cfg(feature = "seller")]
+fn backup_diff() {
+    use crate::seller::backup::{Backup, DifferenceKind, Section};
+    let backup = |subs: &str, vars: &str| Backup::read_from(format!(r#"{{"version":1,"created":1640995200,"settings":null,"subscriptions":{},"licenses":[],"users":[],"variables":{},"blacklists":[],"channels":[],"buttons":[],"errors":[]}}"#, subs, vars).as_bytes()).unwrap();
+    let staging = backup(r#"[{"name":"default","level":"1"},{"name":"vip","level":"2"}]"#, r#"[{"varid":"motd","msg":"hi","authed":"0"}]"#);
+    let production = backup(r#"[{"name":"default","level":"1"},{"name":"vip","level":"3"}]"#, r#"[{"varid":"old","msg":"x","authed":"1"}]"#);
+    let differences: Vec<_> = staging.diff(&production).into_iter().map(|d| (d.section, d.name, d.kind)).collect();
+    assert_eq!(differences, vec![
+        (Section::Subscriptions, "vip".to_string(), DifferenceKind::Changed(vec!["level".to_string()])),
+        (Section::Variables, "motd".to_string(), DifferenceKind::Missing),
+        (Section::Variables, "old".to_string(), DifferenceKind::Extra),
+    ]);
+    assert!(Backup::read_from(r#"{"version":99}"#.as_bytes()).is_err());
+}
+
+#[test]
+#[cfg(feature = "mock")]
+fn backup_snapshot_errors_and_exact_keys() {
+    use crate::seller::backup::{exact_mask, Backup};
+    use crate::seller::mock::{MockApp, MockServer};
+    let server = MockServer::start(MockApp::demo()).unwrap();
+    // the mock has no subscriptions endpoint, that has to fail the snapshot instead of looking empty
+    let err = Backup::snapshot(&server.client()).err().unwrap();
+    assert_eq!(err, "subscriptions: mock server doesnt implement type fetchallsubs");
+    let wrong_key = crate::seller::SellerClient::new("wrong", server.url().to_string());
+    assert_eq!(Backup::snapshot(&wrong_key).err().unwrap(), "settings: Seller key not found");
+    assert!(crate::seller::empty_if_none_found::<()>(Err("No users found".to_string())).unwrap().is_empty());
+    assert!(exact_mask("PRO-ABC123-7Q9").is_ok());
+    assert!(exact_mask("PRO-ABCX23").is_err() && exact_mask("pro-x").is_err() && exact_mask("PRO-****").is_err() && exact_mask("PRO_1").is_err());
+}
+
+#[test]
+#[cfg(feature = "config")]
+fn config_plan() {
+    use crate::seller::config::{Config, Live};
+    let toml = Config::from_toml("whitelist = [\"203.0.113.7\"]\n[subscriptions]\ndefault = 1\nvip = 2\n[variables]\nmotd = \"welcome\"\ndownload = { value = \"https://example.com\", authed = true }\n").unwrap();
+    let yaml = Config::from_yaml("whitelist: [203.0.113.7]\nsubscriptions:\n  default: 1\n  vip: 2\nvariables:\n  motd: welcome\n  download: { value: \"https://example.com\", authed: true }\n").unwrap();
+    assert_eq!(toml, yaml);
+    let live = Live {
+        subscriptions: [("default", 1), ("vip", 1), ("old", 3)].iter().map(|(n, l)| (n.to_string(), *l)).collect(),
+        variables: [("motd", "hi", false), ("download", "https://example.com", false)].iter().map(|(n, v, a)| (n.to_string(), (v.to_string(), *a))).collect(),
+        ..Default::default()
+    };
+    let plan = toml.plan_against(live).unwrap();
+    let actions: Vec<String> = plan.actions.iter().map(|a| a.to_string()).collect();
+    assert_eq!(actions, vec![
+        "~ subscription vip (level 2)",
+        "- subscription old",
+        "- variable download",
+        "+ variable download (authed)",
+        "~ variable motd",
+        "+ whitelist 203.0.113.7",
+    ]);
+    assert!(Config::from_toml("[unknown]\n").is_err());
+}
+
+#[test]
+#[cfg(all(feature = "config", feature = "mock"))]
+fn config_plan_stops_on_fetch_errors() {
+    use crate::seller::config::Config;
+    use crate::seller::mock::{MockApp, MockServer};
+    let server = MockServer::start(MockApp::default()).unwrap();
+    let config = Config::from_toml("[subscriptions]\ndefault = 1\n").unwrap();
+    let wrong_key = crate::seller::SellerClient::new("wrong", server.url().to_string());
+    assert_eq!(config.plan(&wrong_key).err().unwrap(), "Seller key not found");
+    let unreachable = crate::seller::SellerClient::new("key", "http://127.0.0.1:9/".to_string());
+    assert!(config.plan(&unreachable).is_err());
+}
+
+#[test]
+#[cfg(feature = "mock")]
+fn mock_server_users() {
+    use crate::seller::mock::{MockApp, MockServer};
+    let server = MockServer::start(MockApp::demo()).unwrap();
+    let client = server.client();
+    assert_eq!(client.users().fetch_all_users().unwrap().len(), 3);
+    assert_eq!(client.users().stream_all_users().filter(|x| x.is_ok()).count(), 3);
+    client.users().ban("alice", Some("chargeback".to_string())).unwrap();
+    client.users().reset_hwid("alice").unwrap();
+    client.users().extend_user_subscription("bob", "vip", "7", None).unwrap();
+    assert_eq!(client.users().fetch_all_vars().unwrap()[0].data, "dark");
+    assert!(client.sessions().list_all().unwrap()[0].validated);
+    client.sessions().kill("s1").unwrap();
+    assert!(client.users().ban("nobody", None).is_err());
+    let app = server.app();
+    assert_eq!(app.users["alice"].banned.as_deref(), Some("chargeback"));
+    assert_eq!(app.users["alice"].hwid, None);
+    assert!(app.users["bob"].subscriptions["vip"] > std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() + 6 * 86400);
+    assert_eq!(app.sessions.len(), 1);
+}
+
+#[test]
+#[cfg(feature = "seller")]
+fn json_stream_items() {
+    use crate::seller::stream::JsonStream;
+    use serde_json::Value;
+    let body = r#"{"success":true,"message":"ok, \"all\" of them","keys":[{"key":"a]b"} , {"key":"c","n":[1,2,{"x":-1.5e3}]}],"extra":null}"#;
+    let keys: Vec<Value> = JsonStream::new(std::io::Cursor::new(body), "keys").collect::<Result<_, _>>().unwrap();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(keys[0]["key"], "a]b");
+    assert_eq!(keys[1]["n"][2]["x"], -1500.0);
+    assert_eq!(JsonStream::<Value>::new(std::io::Cursor::new(r#"{"success":true,"keys":"No keys"}"#), "keys").count(), 0);
+    let failed: Vec<_> = JsonStream::<Value>::new(std::io::Cursor::new(r#"{"success":false,"message":"Invalid seller key"}"#), "keys").collect();
+    assert_eq!(failed, vec![Err("Invalid seller key".to_string())]);
+    assert!(JsonStream::<Value>::new(std::io::Cursor::new(r#"{"success":true,"keys":[{"key":"#), "keys").any(|x| x.is_err()));
+}
+
+/// a seller api that answers each request type with its fixture body, so the client code picks the type and the list key
+#[cfg(feature = "seller")]
+fn seller_fixtures(fixtures: &[(&str, &str)]) -> crate::seller::SellerClient {
+    use std::io::{BufRead, BufReader, Write};
+    let fixtures: std::collections::HashMap<String, String> = fixtures.iter().map(|(ty, body)| (ty.to_string(), body.to_string())).collect();
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = String::new();
+            let _ = BufReader::new(&mut stream).read_line(&mut request);
+            let query = request.split(' ').nth(1).and_then(|path| path.split_once('?')).map(|(_, query)| query).unwrap_or_default();
+            let ty = query.split('&').find_map(|p| p.strip_prefix("type=")).unwrap_or_default();
+            let body = fixtures.get(ty).cloned().unwrap_or_else(|| format!(r#"{{"success":false,"message":"no fixture for {}"}}"#, ty));
+            let _ = write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body);
+        }
+    });
+    crate::seller::SellerClient::new("sellerkey", url)
+}
+
+#[test]
+#[cfg(feature = "seller")]
+fn typed_list_fixtures() {
+    use std::time::{Duration, UNIX_EPOCH};
+    let client = seller_fixtures(&[
+        ("fetchallsessions", r#"{"success":true,"message":"Successfully retrieved sessions","sessions":[
+            {"id":"s1","credential":"alice","expiry":"1700000000","validated":"1","ip":"203.0.113.7"},
+            {"id":"s2","credential":null,"expiry":1700000600,"validated":0,"ip":""}]}"#),
+        ("fetchallmutes", r#"{"success":true,"mutes":[{"user":"bob","time":"1700003600"}]}"#),
+        ("fetchalluservars", r#"{"success":true,"vars":[{"name":"theme","data":"dark","user":"alice","readOnly":"1"},{"name":"lang","data":"en","user":"bob"}]}"#),
+    ]);
+    let sessions = client.sessions().list_all().unwrap();
+    assert_eq!(sessions[0].expiry, UNIX_EPOCH + Duration::from_secs(1700000000));
+    assert!(sessions[0].validated && !sessions[1].validated);
+    assert_eq!((sessions[1].credential.as_deref(), sessions[1].ip.as_deref()), (None, None));
+    let mutes = client.chat().fetch_all_mutes().unwrap();
+    assert_eq!(mutes[0].until, UNIX_EPOCH + Duration::from_secs(1700003600));
+    let vars = client.users().fetch_all_vars().unwrap();
+    assert!(vars[0].read_only && !vars[1].read_only);
+
+    let empty = seller_fixtures(&[
+        ("fetchallmutes", r#"{"success":true,"message":"No mutes","mutes":"No mutes"}"#),
+        ("fetchalluservars", r#"{"success":true,"vars":[]}"#),
+        ("fetchallsessions", r#"{"success":false,"message":"No active sessions"}"#),
+    ]);
+    assert!(empty.chat().fetch_all_mutes().unwrap().is_empty());
+    assert!(empty.users().fetch_all_vars().unwrap().is_empty());
+    assert_eq!(empty.sessions().list_all(), Err("No active sessions".to_string()));
+}
+
+#[test]
+#[cfg(feature = "seller")]
+fn typed_list_fixtures_rest() {
+    use crate::seller::subscriptions::Sub;
+    use std::time::{Duration, UNIX_EPOCH};
+    let client = seller_fixtures(&[
+        ("fetchallusers", r#"{"success":true,"users":[
+            {"id":"1","username":"alice","email":null,"password":"$2y$hash","hwid":"HWID-1","app":"app","owner":"seller","createdate":"1640995200","lastlogin":"1641081600","banned":null,"ip":"203.0.113.7","cooldown":null},
+            {"id":"2","username":"bob","email":"","password":null,"hwid":"","app":"app","owner":"seller","createdate":1640995200,"lastlogin":null,"banned":"chargeback","ip":null,"cooldown":"0"}]}"#),
+        ("userdata", r#"{"success":true,"username":"alice","subscriptions":[{"subscription":"default","expiry":"1700000000","key":null}],"ip":"203.0.113.7","hwid":null,"createdate":"1640995200","lastlogin":null,"cooldown":null,"token":"t"}"#),
+        ("fetchallsubs", r#"{"success":true,"subs":[{"name":"default","level":"1"},{"name":"vip","level":3}]}"#),
+        ("fetchallvars", r#"{"success":true,"vars":[{"varid":"motd","msg":"hi","authed":"1"},{"varid":"dl","msg":"x","authed":0}]}"#),
+        ("fetchallblacks", r#"{"success":true,"blacklists":[{"hwid":"HWID-1","ip":null,"type":"hwid"},{"hwid":"","ip":"198.51.100.3","type":"ip"}]}"#),
+        ("fetchallchats", r#"{"success":true,"chats":[{"name":"general","delay":"5"}]}"#),
+        ("fetchallbuttons", r#"{"success":true,"buttons":[{"text":"Download","value":"dl"}]}"#),
+    ]);
+    let users = client.users().fetch_all_users().unwrap();
+    assert_eq!(users[0].lastlogin, Some(UNIX_EPOCH + Duration::from_secs(1641081600)));
+    assert_eq!((users[1].lastlogin, users[1].hwid.as_deref(), users[1].email.as_deref(), users[1].cooldown), (None, None, None, None));
+    assert_eq!(users[1].banned.as_deref(), Some("chargeback"));
+    assert_eq!(users[0].clone(), users[0]);
+    let data = client.users().user_data("alice").unwrap();
+    assert!(data.subscriptions[0].is_active_at(UNIX_EPOCH + Duration::from_secs(1699999999)));
+    assert!(!data.subscriptions[0].is_active_at(UNIX_EPOCH + Duration::from_secs(1700000000)));
+    let subs = client.subscriptions().fetch_all().unwrap();
+    assert_eq!(subs[1], Sub { name: "vip".to_string(), level: 3 });
+    let vars = client.variables().fetch_all().unwrap();
+    assert!(vars[0].authed && !vars[1].authed);
+    let blacklists = client.blacklists().fetch_all().unwrap();
+    assert_eq!((blacklists[1].hwid.as_deref(), blacklists[1].ip.as_deref()), (None, Some("198.51.100.3")));
+    let channels = client.chat().fetch_all_channels().unwrap();
+    assert_eq!(channels[0].delay, 5);
+    let buttons = client.web_loader().retrieve_all_buttons().unwrap();
+    assert_eq!(buttons[0].value, "dl");
+
+    let empty = seller_fixtures(&[
+        ("fetchallusers", r#"{"success":false,"message":"No users found"}"#),
+        ("fetchallsubs", r#"{"success":true,"subs":[]}"#),
+        ("fetchallvars", r#"{"success":true,"vars":"No variables"}"#),
+        ("fetchallblacks", r#"{"success":true,"blacklists":[]}"#),
+        ("fetchallchats", r#"{"success":true,"chats":[]}"#),
+        ("fetchallbuttons", r#"{"success":true,"buttons":"No buttons"}"#),
+    ]);
+    assert_eq!(empty.users().fetch_all_users(), Err("No users found".to_string()));
+    assert!(empty.subscriptions().fetch_all().unwrap().is_empty());
+    assert!(empty.variables().fetch_all().unwrap().is_empty());
+    assert!(empty.blacklists().fetch_all().unwrap().is_empty());
+    assert!(empty.chat().fetch_all_channels().unwrap().is_empty());
+    assert!(empty.web_loader().retrieve_all_buttons().unwrap().is_empty());
+}
+
+#[test]
+#[cfg(feature = "mock")]
+fn user_data_and_count_errors() {
+    use crate::seller::mock::{MockApp, MockServer};
+    let server = MockServer::start(MockApp::demo()).unwrap();
+    let client = server.client();
+    assert_eq!(client.users().user_data("alice").unwrap().subscriptions[0].subscription, "default");
+    assert_eq!(client.users().user_data("nobody").err().unwrap(), "User not found");
+    assert_eq!(client.users().count_subscriptions("default"), Ok(2));
+    let wrong_key = crate::seller::SellerClient::new("wrong", server.url().to_string());
+    assert_eq!(wrong_key.users().count_subscriptions("default"), Err("Seller key not found".to_string()));
+}
+
+#[test]
+#[cfg(feature = "mock")]
+fn settings_patch_round_trip() {
+    use crate::seller::mock::{MockApp, MockServer};
+    use crate::seller::settings::SettingsPatch;
+    let server = MockServer::start(MockApp::default()).unwrap();
+    let client = server.client();
+    let before = client.settings().retrieve().unwrap();
+    let mut patch = SettingsPatch::default();
+    patch.set("hwid-lock", "true").unwrap();
+    patch.set("disabledmsg", "down for maintenance").unwrap();
+    assert!(patch.set("cooldown", "1").is_err() && patch.set("enabled", "maybe").is_err());
+    client.settings().patch(&patch).unwrap();
+    let after = client.settings().retrieve().unwrap();
+    assert_eq!(before.diff(&after), patch);
+    // sellerkey, type and the two changed settings
+    assert_eq!(server.app().requests[1].len(), 4);
+
+    let mut wanted = after.clone();
+    wanted.weekresellerproductid = "week".to_string();
+    wanted.monthresellerproductid = "month".to_string();
+    wanted.liferesellerproductid = "life".to_string();
+    client.settings().update(wanted.clone()).unwrap();
+    assert_eq!(client.settings().retrieve().unwrap(), wanted);
+    assert!(client.settings().patch(&SettingsPatch::default()).is_err());
+}
+
+#[test]
+#[cfg(feature = "mock")]
+fn settings_patch_parameter_names() {
+    use crate::seller::mock::{MockApp, MockServer};
+    use crate::seller::settings::SettingsPatch;
+    let server = MockServer::start(MockApp::default()).unwrap();
+    let message = server.client().settings().patch(&SettingsPatch::from(MockApp::default().settings)).unwrap();
+    assert!(!message.starts_with('"'));
+    // written out by hand so a typo in the settings_patch table cant hide behind the mock copying it
+    let expected = [
+        "appdisabled", "dayproduct", "download", "enabled", "hwidblacked", "hwidcheck", "hwidmismatch", "keyexpired",
+        "keynotfound", "keypaused", "keyused", "lifetimeproduct", "monthproduct", "noactivesubs", "nosublevel",
+        "passmismatch", "resellerstore", "sellerkey", "sellixsecret", "type", "usernamenotfound", "usernametaken",
+        "ver", "webhook", "weekproduct",
+    ];
+    let sent: Vec<String> = server.app().requests[0].keys().cloned().collect();
+    assert_eq!(sent, expected);
+}
+
+#[test]
+#[cfg(feature = "mock")]
+fn bulk_ban_selection() {
+    use crate::seller::bulk::{Bulk, Operation, UserSelection};
+    use crate::seller::mock::{MockApp, MockServer};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    let server = MockServer::start(MockApp::demo()).unwrap();
+    let client = server.client();
+    let mut users = UserSelection::new().subscription("default").banned(false).select(&client).unwrap();
+    assert_eq!(users, vec!["alice".to_string()]);
+    assert!(UserSelection::new().inactive_for(std::time::Duration::from_secs(86400)).select(&client).unwrap().is_empty());
+
+    users.push("nobody".to_string());
+    let calls = AtomicUsize::new(0);
+    let report = Bulk::new(&client)
+        .concurrency(2)
+        .on_progress(|p| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            assert!(p.done <= p.total);
+        })
+        .run(&users, &Operation::Ban { reason: Some("bulk".to_string()) });
+    assert_eq!(calls.load(Ordering::Relaxed), 2);
+    assert_eq!(report.results[1].result, Err("User not found".to_string()));
+    assert_eq!((report.succeeded().count(), report.results[1].attempts), (1, 1));
+    assert_eq!(server.app().users["alice"].banned.as_deref(), Some("bulk"));
+}
+
+#[test]
+#[cfg(feature = "seller")]
+fn bulk_retries_unreachable() {
+    use crate::seller::bulk::{Bulk, Operation};
+    use crate::seller::SellerClient;
+    let client = SellerClient::new("sellerkey", "http://127.0.0.1:9/".to_string());
+    let report = Bulk::new(&client)
+        .retries(2)
+        .retry_delay(std::time::Duration::from_millis(1))
+        .run(&["alice".to_string()], &Operation::Unban);
+    assert_eq!(report.results[0].attempts, 3);
+    assert!(report.results[0].result.is_err());
+    assert!(client.users().fetch_all_users().is_err());
+    assert_eq!(client.retried(), 0);
+
+    // extending twice would add the days twice, so it isnt retried unless asked for
+    let extend = Operation::Extend { sub: "default".to_string(), days: 7, active_only: false };
+    assert_eq!(Bulk::new(&client).run(&["alice".to_string()], &extend).results[0].attempts, 1);
+}
+
+#[test]
+#[cfg(feature = "seller")]
+fn seller_retries_only_failed_connects() {
+    use crate::seller::SellerClient;
+    use std::io::Read;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    // reads the request and hangs up without answering, keyauth could have handled it
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counted = requests.clone();
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let _ = stream.read(&mut [0u8; 4096]);
+            counted.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+    let client = SellerClient::new("sellerkey", url).with_retries(2, std::time::Duration::from_millis(1));
+    assert!(client.users().extend_user_subscription("alice", "default", "7", None).is_err());
+    assert_eq!(client.retried(), 0);
+    assert_eq!(requests.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn rate_limiter_bucket() {
+    use std::time::{Duration, Instant};
+    let limiter = crate::RateLimiter::new(20.0, 2);
+    let shared = limiter.clone();
+    assert!(limiter.try_acquire() && shared.try_acquire());
+    assert!(!limiter.try_acquire());
+    let start = Instant::now();
+    shared.acquire();
+    assert!(start.elapsed() >= Duration::from_millis(30));
+}
+
+#[test]
+#[cfg(feature = "mock")]
+fn rate_limiter_backs_off_throttled_seller_calls() {
+    use crate::seller::mock::{MockApp, MockServer};
+    let server = MockServer::start(MockApp::demo()).unwrap();
+    server.app().throttle = 2;
+    let client = server.client().with_rate_limiter(crate::RateLimiter::new(50.0, 5));
+    assert_eq!(client.users().fetch_all_users().unwrap().len(), 3);
+    assert_eq!(server.app().throttle, 0);
+}
+
+#[test]
+#[cfg(feature = "tracing")]
+fn trace_params_are_redacted() {
+    use std::collections::HashMap;
+    let params = HashMap::from([("type", "login"), ("username", "alice"), ("pass", "hunter2"), ("sellerkey", "abc"), ("key", "KEY-123")]);
+    assert_eq!(crate::trace::redact(&params), "key=<redacted> pass=<redacted> sellerkey=<redacted> type=login username=alice");
+}
+
+/// answers one request with extra headers and body, returns the url
+#[cfg(feature = "v1_2")]
+fn respond_once(headers: String, body: String) -> (String, std::thread::JoinHandle<()>) {
+    use std::io::{Read, Write};
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let _ = stream.read(&mut [0u8; 4096]);
+        write!(stream, "HTTP/1.1 200 OK\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}", headers, body.len(), body).unwrap();
+    });
+    (url, server)
+}
+
+/// runs f, with the panic feature tampered responses panic instead of returning an error
+#[cfg(feature = "v1_2")]
+fn rejected<T, E>(f: impl FnOnce() -> Result<T, E>) -> bool {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).map_or(true, |result| result.is_err())
+}
+
+#[test]
+#[cfg(feature = "v1_2")]
+fn metrics_count_tampered_init() {
+    use crate::metrics::{ErrorKind, InMemoryMetrics, Metrics, Outcome, RequestEvent};
+    use std::sync::Arc;
+    // no signature header
+    let (url, server) = respond_once(String::new(), r#"{"success":true,"message":"Initialized"}"#.to_string());
+    let metrics = Arc::new(InMemoryMetrics::new());
+    let mut auth = crate::v1_2::KeyauthApi::new("app", "owner", "secret", "1.0", &url);
+    auth.metrics = Some(metrics.clone());
+    assert!(rejected(|| auth.init(None)));
+    server.join().unwrap();
+    assert_eq!(metrics.requests("init", Outcome::Error(ErrorKind::Tampered)), 1);
+
+    metrics.record(&RequestEvent { api: "1.2", action: "login", latency: std::time::Duration::from_millis(80), outcome: Outcome::Failed });
+    let text = metrics.render();
+    assert!(text.contains("keyauth_tamper_detections_total{api=\"1.2\",action=\"init\"} 1"));
+    assert!(text.contains("keyauth_errors_total{api=\"1.2\",action=\"init\",kind=\"tampered\"} 1"));
+    assert!(text.contains("keyauth_requests_total{api=\"1.2\",action=\"login\",outcome=\"failed\"} 1"));
+    assert!(text.contains("keyauth_request_duration_seconds_bucket{api=\"1.2\",action=\"login\",le=\"0.05\"} 0"));
+    assert!(text.contains("keyauth_request_duration_seconds_bucket{api=\"1.2\",action=\"login\",le=\"0.1\"} 1"));
+    assert!(text.contains("keyauth_request_duration_seconds_count{api=\"1.2\",action=\"login\"} 1"));
+}
+
+#[test]
+#[cfg(feature = "v1_2")]
+fn metrics_count_file_and_log_outcomes() {
+    use crate::metrics::{ErrorKind, InMemoryMetrics, Outcome};
+    use std::sync::Arc;
+    let metrics = Arc::new(InMemoryMetrics::new());
+    let api = |url: &str| {
+        let mut auth = crate::v1_2::KeyauthApi::new("app", "owner", "secret", "1.0", url);
+        auth.metrics = Some(metrics.clone());
+        auth
+    };
+
+    // the file arrives but is signed with the wrong key
+    let body = r#"{"success":true,"message":"","contents":"6869"}"#;
+    let signature = hex::encode(hmac_sha256::HMAC::mac(body, "not the session key"));
+    let (url, server) = respond_once(format!("signature: {}\r\n", signature), body.to_string());
+    let mut auth = api(&url);
+    assert!(rejected(|| auth.file("fileid".to_string())));
+    server.join().unwrap();
+    assert_eq!(metrics.requests("file", Outcome::Error(ErrorKind::Tampered)), 1);
+    assert_eq!(metrics.requests("file", Outcome::Success), 0);
+
+    // correctly signed but success false
+    let body = r#"{"success":false,"message":"File not found"}"#;
+    let signature = hex::encode(hmac_sha256::HMAC::mac(body, ""));
+    let (url, server) = respond_once(format!("signature: {}\r\n", signature), body.to_string());
+    assert_eq!(api(&url).file("fileid".to_string()), Err("File not found".to_string()));
+    server.join().unwrap();
+    assert_eq!(metrics.requests("file", Outcome::Failed), 1);
+
+    let (url, server) = respond_once(String::new(), r#"{"success":false,"message":"Session not found"}"#.to_string());
+    api(&url).log("hello".to_string(), None);
+    server.join().unwrap();
+    assert_eq!(metrics.requests("log", Outcome::Failed), 1);
+    assert_eq!(metrics.requests("log", Outcome::Success), 0);
+}
+
+#[test]
+#[cfg(feature = "v1_2")]
+fn file_scanner_chunk_boundaries() {
+    use crate::v1_2::FileScanner;
+    let scan = |chunks: &[&[u8]]| {
+        let mut scanner = FileScanner::default();
+        let mut out = Vec::new();
+        for chunk in chunks {
+            scanner.feed(chunk, &mut out)?;
+        }
+        Ok::<_, String>((out, scanner))
+    };
+    let body = br#"{"success":true,"message":"","contents":"48656c6C6f","x":1}"#;
+    // every way to cut the body in three, so the key and hex digit pairs get split too
+    for i in 0..=body.len() {
+        for j in i..=body.len() {
+            let (out, scanner) = scan(&[&body[..i], &body[i..j], &body[j..]]).unwrap();
+            assert_eq!(out, b"Hello", "split at {} and {}", i, j);
+            assert_eq!(scanner.skeleton, br#"{"success":true,"message":"","contents":"","x":1}"#);
+            assert_eq!(scanner.nibble, None);
+        }
+    }
+
+    let (out, scanner) = scan(&[br#"{"contents":"48"#, br#"6"}"#]).unwrap();
+    assert_eq!((out, scanner.nibble), (b"H".to_vec(), Some(6)));
+    assert!(scan(&[br#"{"contents":"4g"}"#]).is_err());
+    let (out, scanner) = scan(&[b"KeyAuth_", b"Invalid"]).unwrap();
+    assert!(out.is_empty());
+    assert_eq!(scanner.skeleton, b"KeyAuth_Invalid");
+}
+
+#[test]
+#[cfg(feature = "v1_2")]
+fn file_cache_hits_and_misses() {
+    let dir = std::env::temp_dir().join(format!("keyauth-file-cache-hit-test-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("broken")).unwrap();
+    std::fs::write(dir.join("fileid"), b"cached contents").unwrap();
+    let sha256 = hex::encode(hmac_sha256::Hash::hash(b"cached contents"));
+    // nothing listens there, so every miss is a failed download
+    let mut auth = crate::v1_2::KeyauthApi::new("app", "owner", "secret", "1.0", "http://127.0.0.1:9/");
+    auth.file_cache = Some(dir.clone());
+    let mut read = |fileid: &str, sha256: Option<&str>| {
+        let mut out = Vec::new();
+        auth.file_to_writer(fileid.to_string(), &mut out, sha256, None).map(|_| out)
+    };
+
+    assert_eq!(read("fileid", Some(&sha256)), Ok(b"cached contents".to_vec()));
+    assert_eq!(read("fileid", Some(&sha256.to_uppercase())), Ok(b"cached contents".to_vec()));
+    assert!(read("fileid", None).is_err());
+    assert!(read("fileid", Some(&"0".repeat(64))).is_err());
+    // a directory cant be read as the cached file, that is a miss and not an io error
+    let err = read("broken", Some(&sha256)).unwrap_err();
+    assert!(!err.contains("directory"), "{}", err);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "v1_2")]
+fn file_download_errors_leave_no_cache() {
+    let dir = std::env::temp_dir().join(format!("keyauth-file-cache-test-{}", std::process::id()));
+    let download = |headers: String, body: &str| {
+        let (url, server) = respond_once(headers, body.to_string());
+        let mut auth = crate::v1_2::KeyauthApi::new("app", "owner", "secret", "1.0", &url);
+        auth.file_cache = Some(dir.clone());
+        let mut out = Vec::new();
+        let rejected = rejected(|| auth.file_to_writer("fileid".to_string(), &mut out, None, None));
+        server.join().unwrap();
+        assert!(rejected);
+        assert!(!dir.join("fileid").exists() && !dir.join("fileid.part").exists());
+    };
+    let signed = |body: &str| format!("signature: {}\r\n", hex::encode(hmac_sha256::HMAC::mac(body, "")));
+
+    let body = r#"{"success":true,"message":"","contents":"6869"}"#;
+    download(format!("signature: {}\r\n", hex::encode(hmac_sha256::HMAC::mac(body, "wrong"))), body);
+    let body = r#"{"success":true,"message":"","contents":"686"}"#;
+    download(signed(body), body);
+    let body = r#"{"success":true,"message":"","contents":"6x"}"#;
+    download(signed(body), body);
+    download(signed("KeyAuth_Invalid"), "KeyAuth_Invalid");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// a 1.2 api that answers every request with answer(form body, number of earlier requests of that type),
+/// signed with the session key of an api that didnt init. returns the url and the number of requests so far
+#[cfg(feature = "v1_2")]
+fn keyauth_server<F>(answer: F) -> (String, std::sync::Arc<std::sync::Mutex<std::collections::HashMap<String, usize>>>)
+where
+    F: Fn(&str, usize) -> String + Send + 'static,
+{
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::sync::{Arc, Mutex};
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let counts = Arc::new(Mutex::new(HashMap::<String, usize>::new()));
+    let server_counts = counts.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut reader = BufReader::new(&mut stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap_or(0);
+                    }
+                }
+            }
+            let mut form = vec![0u8; length];
+            let _ = reader.read_exact(&mut form);
+            let form = String::from_utf8_lossy(&form).to_string();
+            let ty = form.split('&').find_map(|p| p.strip_prefix("type=")).unwrap_or_default().to_string();
+            let n = {
+                let mut counts = server_counts.lock().unwrap();
+                let count = counts.entry(ty).or_default();
+                *count += 1;
+                *count - 1
+            };
+            let body = answer(&form, n);
+            let signature = hex::encode(hmac_sha256::HMAC::mac(&body, ""));
+            let _ = write!(stream, "HTTP/1.1 200 OK\r\nsignature: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", signature, body.len(), body);
+        }
+    });
+    (url, counts)
+}
+
+#[test]
+#[cfg(feature = "v1_2")]
+fn chat_channel_polls_dedups_and_stops() {
+    use std::time::{Duration, Instant};
+    let message = |author: &str, text: &str, timestamp: u64| format!(r#"{{"author":"{}","message":"{}","timestamp":"{}"}}"#, author, text, timestamp);
+    let (a, b, c) = (message("alice", "hi", 100), message("bob", "yo", 101), message("carol", "hey", 102));
+    // the same text again later is a new message
+    let d = message("alice", "hi", 103);
+    let (url, counts) = keyauth_server(move |form, n| {
+        if form.contains("type=chatsend") {
+            let reply = if form.contains("message=spam") {
+                r#"{"success":false,"message":"You're muted from chat until 2030"}"#
+            } else {
+                r#"{"success":true,"message":"Message sent"}"#
+            };
+            return reply.to_string();
+        }
+        let messages = match n {
+            0 => vec![&a, &b],
+            1 => vec![&a, &b, &c],
+            _ => vec![&b, &c, &d],
+        };
+        format!(r#"{{"success":true,"message":"","messages":[{}]}}"#, messages.into_iter().cloned().collect::<Vec<_>>().join(","))
+    });
+    let polls = || counts.lock().unwrap().get("chatget").copied().unwrap_or_default();
+    let auth = crate::v1_2::KeyauthApi::new("app", "owner", "secret", "1.0", &url);
+    let mut channel = auth.chat_channel("general", Duration::from_secs(60), Duration::from_millis(20));
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while polls() < 6 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let received: Vec<_> = channel.new_messages().map(|m| (m.author, m.message)).collect();
+    let pair = |author: &str, text: &str| (author.to_string(), text.to_string());
+    assert_eq!(received, [pair("alice", "hi"), pair("bob", "yo"), pair("carol", "hey"), pair("alice", "hi")]);
+
+    assert_eq!(channel.send("spam"), Err("You're muted from chat until 2030".to_string()));
+    assert_eq!(channel.muted(), Some("You're muted from chat until 2030"));
+    assert_eq!(channel.send("hello"), Ok(()));
+    assert_eq!(channel.muted(), None);
+    assert!(channel.send("again").unwrap_err().starts_with("channel delay"));
+    assert_eq!(counts.lock().unwrap()["chatsend"], 2);
+
+    drop(channel);
+    // a poll can still be in flight
+    std::thread::sleep(Duration::from_millis(100));
+    let stopped = polls();
+    std::thread::sleep(Duration::from_millis(200));
+    assert_eq!(polls(), stopped);
+}
+
+#[test]
+#[cfg(feature = "v1_2")]
+fn debug_redacts_secrets() {
+    let secret = crate::Secret::from("hunter2");
+    assert_eq!(format!("{:?}", secret), "<redacted>");
+    assert_eq!(format!("{:?}", crate::Secret::default()), "\"\"");
+    assert_eq!(secret.as_str(), "hunter2");
+
+    let auth = crate::v1_2::KeyauthApi::new("app", "owner", "appsecret123", "1.0", "https://keyauth.win/api/1.2/");
+    let debug = format!("{:?}", auth);
+    assert!(debug.contains("name: \"app\""));
+    assert!(debug.contains("secret: <redacted>"));
+    assert!(!debug.contains("appsecret123"));
+    #[cfg(feature = "seller")]
+    {
+        let client = crate::seller::SellerClient::new("sellerkey123", "https://keyauth.win/api/seller/".to_string());
+        assert!(!format!("{:?}", client).contains("sellerkey123"));
+    }
+}
+
+#[test]
+#[cfg(feature = "obfuscate")]
+fn app_macro_signs_with_sealed_secret() {
+    use crate::metrics::{InMemoryMetrics, Outcome};
+    use std::sync::Arc;
+    let sealed = crate::sealed!("appsecret123");
+    assert_eq!(sealed.reveal().as_str(), "appsecret123");
+    assert_eq!(crate::Sealed::new("hunter2").reveal().as_str(), "hunter2");
+
+    // signed with the app secret, a wrong secret would be a tamper error (a panic with the panic feature)
+    let body = r#"{"success":false,"message":"application disabled"}"#;
+    let signature = hex::encode(hmac_sha256::HMAC::mac(body, "appsecret123"));
+    let (url, server) = respond_once(format!("signature: {}\r\n", signature), body.to_string());
+    let metrics = Arc::new(InMemoryMetrics::new());
+    let mut auth = crate::app! {
+        name: "app",
+        ownerid: "owner",
+        secret: "appsecret123",
+        version: "1.0",
+        url: &url,
+    };
+    auth.metrics = Some(metrics.clone());
+    assert!(!format!("{:?}", auth).contains("appsecret123"));
+    assert!(auth.init(None).is_err());
+    server.join().unwrap();
+    assert_eq!(metrics.requests("init", Outcome::Failed), 1);
+}
@@ -0,0 +1,119 @@
+//! a tracing span per keyauth request for the tracing feature, without the feature everything here does nothing.
+//! the span is called `keyauth` and has the fields api, type, params (secrets redacted), status, success, signature and latency_ms,
+//! an event is emitted in it when the request is done (debug) or failed (warn)
+#![cfg_attr(not(feature = "tracing"), allow(dead_code, unused_variables))]
+
+use serde::Serialize;
+#[cfg(feature = "tracing")]
+use std::time::Instant;
+
+/// parameters whose values are never logged
+const SECRET_PARAMS: &[&str] = &["secret", "sellerkey", "pass", "password", "enckey", "key", "license", "sessionid", "token"];
+
+/// the parameters as `name=value` pairs, secrets are replaced with `<redacted>`
+pub(crate) fn redact<P: Serialize>(params: &P) -> String {
+    let Ok(serde_json::Value::Object(params)) = serde_json::to_value(params) else {
+        return String::new();
+    };
+    let mut pairs: Vec<String> = params.iter().map(|(name, value)| {
+        let value = match value {
+            _ if SECRET_PARAMS.contains(&name.as_str()) => "<redacted>".to_string(),
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        format!("{}={}", name, value)
+    }).collect();
+    // hashmaps have no order
+    pairs.sort();
+    pairs.join(" ")
+}
+
+/// span of one request, the done/failed event is emitted when it is dropped
+pub(crate) struct Call {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing")]
+    start: Instant,
+    #[cfg(feature = "tracing")]
+    error: Option<String>,
+    /// end of the body read so far, to find the success flag in it
+    #[cfg(feature = "tracing")]
+    tail: Vec<u8>,
+    #[cfg(feature = "tracing")]
+    success_seen: bool,
+}
+
+impl Call {
+    pub(crate) fn start<P: Serialize>(api: &'static str, ty: &str, params: &P) -> Call {
+        #[cfg(feature = "tracing")]
+        {
+            use tracing::field::Empty;
+            let span = tracing::info_span!("keyauth", api, r#type = ty, params = Empty, status = Empty, success = Empty, signature = Empty, latency_ms = Empty);
+            if !span.is_disabled() {
+                span.record("params", redact(params).as_str());
+            }
+            Call { span, start: Instant::now(), error: None, tail: Vec::new(), success_seen: false }
+        }
+        #[cfg(not(feature = "tracing"))]
+        Call {}
+    }
+
+    pub(crate) fn status(&self, status: u16) {
+        #[cfg(feature = "tracing")]
+        self.span.record("status", status);
+    }
+
+    /// "valid", "invalid" or "missing"
+    pub(crate) fn signature(&self, outcome: &'static str) {
+        #[cfg(feature = "tracing")]
+        self.span.record("signature", outcome);
+    }
+
+    pub(crate) fn success(&mut self, success: bool) {
+        #[cfg(feature = "tracing")]
+        {
+            self.success_seen = true;
+            self.span.record("success", success);
+        }
+    }
+
+    pub(crate) fn error(&mut self, error: &str) {
+        #[cfg(feature = "tracing")]
+        {
+            self.error = Some(error.to_string());
+        }
+    }
+
+    /// looks for the top level success flag in the body while it is read
+    pub(crate) fn scan(&mut self, bytes: &[u8]) {
+        #[cfg(feature = "tracing")]
+        {
+            const TRUE: &[u8] = b"\"success\":true";
+            const FALSE: &[u8] = b"\"success\":false";
+            if self.success_seen || self.span.is_disabled() {
+                return;
+            }
+            self.tail.extend(bytes.iter().filter(|b| !b.is_ascii_whitespace()));
+            if self.tail.windows(TRUE.len()).any(|w| w == TRUE) {
+                self.success(true);
+            } else if self.tail.windows(FALSE.len()).any(|w| w == FALSE) {
+                self.success(false);
+            } else {
+                let keep = self.tail.len().saturating_sub(FALSE.len());
+                self.tail.drain(..keep);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for Call {
+    fn drop(&mut self) {
+        self.span.record("latency_ms", self.start.elapsed().as_millis() as u64);
+        let _entered = self.span.enter();
+        match &self.error {
+            Some(error) => tracing::warn!(error = error.as_str(), "keyauth request failed"),
+            None => tracing::debug!("keyauth request done"),
+        }
+    }
+}
@@ -0,0 +1,182 @@
+/*!
+replaces the running executable with the version from [`UpdateRequired`], use it when init returns `Err(InitError::UpdateRequired(..))`
+
+```rust,no_run
+let mut auth = keyauth::v1_2::KeyauthApi::new("application name", "ownerid", "application secret", "application version", "https://keyauth.win/api/1.2/");
+match auth.init(None) {
+    Err(keyauth::InitError::UpdateRequired(update)) => {
+        // expected hash of the new version, for example from a global variable
+        keyauth::updater::update_and_restart(&update, Some("sha256 of the new binary")).unwrap();
+    }
+    other => other.unwrap(),
+}
+```
+
+or let [`init_with_hook`] pass it to an [`UpdateHook`], if you dont want to replace the binary use [`OpenBrowser`] to get the old behaviour
+```rust,no_run
+use keyauth::updater::{init_with_hook, OpenBrowser};
+let mut auth = keyauth::v1_2::KeyauthApi::new("application name", "ownerid", "application secret", "application version", "https://keyauth.win/api/1.2/");
+init_with_hook(&mut auth, None, &mut OpenBrowser).unwrap();
+```
+*/
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use hmac_sha256::Hash;
+use reqwest::blocking::Client;
+use crate::{InitError, KeyauthClient, UpdateRequired};
+
+/// what to do when an update is required, implemented for closures so anything can be plugged in
+pub trait UpdateHook {
+    fn update_required(&mut self, update: &UpdateRequired) -> Result<(), String>;
+}
+
+impl<F: FnMut(&UpdateRequired) -> Result<(), String>> UpdateHook for F {
+    fn update_required(&mut self, update: &UpdateRequired) -> Result<(), String> {
+        self(update)
+    }
+}
+
+/// opens the download url in the browser
+pub struct OpenBrowser;
+
+impl UpdateHook for OpenBrowser {
+    fn update_required(&mut self, update: &UpdateRequired) -> Result<(), String> {
+        update.open_in_browser()
+    }
+}
+
+/// downloads the new version, replaces the running executable and restarts it
+pub struct SelfUpdate {
+    /// expected hex sha256 of the new executable, strongly recommended since the download url is just a link
+    pub sha256: Option<String>,
+}
+
+impl UpdateHook for SelfUpdate {
+    fn update_required(&mut self, update: &UpdateRequired) -> Result<(), String> {
+        update_and_restart(update, self.sha256.as_deref())
+    }
+}
+
+/// downloads the new version next to the running executable and returns its path, Err if the sha256 doesnt match
+pub fn download(update: &UpdateRequired, sha256: Option<&str>) -> Result<PathBuf, String> {
+    if update.download_url.is_empty() {
+        return Err("no download url set".to_string());
+    }
+    let exe = current_exe()?;
+    let new = sibling(&exe, "new");
+    let result = download_to(&update.download_url, &new, sha256);
+    if result.is_err() {
+        let _ = fs::remove_file(&new);
+    }
+    result.map(|_| new)
+}
+
+/// atomically replaces the running executable with new, new has to be on the same filesystem (download puts it next to the executable)
+pub fn replace_current_exe(new: &Path) -> Result<(), String> {
+    let exe = current_exe()?;
+    let permissions = fs::metadata(&exe).map_err(|e| e.to_string())?.permissions();
+    fs::set_permissions(new, permissions).map_err(|e| e.to_string())?;
+    // a running exe cant be overwritten on windows, but it can be renamed
+    #[cfg(windows)]
+    let old = {
+        let old = sibling(&exe, "old");
+        let _ = fs::remove_file(&old);
+        fs::rename(&exe, &old).map_err(|e| e.to_string())?;
+        old
+    };
+    let result = fs::rename(new, &exe);
+    // put the old exe back so there still is one to start
+    #[cfg(windows)]
+    if result.is_err() {
+        let _ = fs::rename(&old, &exe);
+    }
+    result.map_err(|e| e.to_string())
+}
+
+/// starts the running executable again with the same arguments, see [`restart_at`]
+pub fn restart() -> Result<(), String> {
+    restart_at(&current_exe()?)
+}
+
+/// starts exe with the arguments of this process, on unix the process is replaced and this only returns on error.
+/// after replace_current_exe use the path from before the replace, on linux the running executable is then a deleted file
+pub fn restart_at(exe: &Path) -> Result<(), String> {
+    let mut command = std::process::Command::new(exe);
+    command.args(std::env::args_os().skip(1));
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        Err(command.exec().to_string())
+    }
+    #[cfg(not(unix))]
+    {
+        command.spawn().map_err(|e| e.to_string())?;
+        std::process::exit(0);
+    }
+}
+
+/// download, replace_current_exe and restart in one go
+pub fn update_and_restart(update: &UpdateRequired, sha256: Option<&str>) -> Result<(), String> {
+    let new = download(update, sha256)?;
+    replace_and_restart(&new)
+}
+
+pub(crate) fn replace_and_restart(new: &Path) -> Result<(), String> {
+    let exe = current_exe()?;
+    replace_current_exe(new)?;
+    restart_at(&exe)
+}
+
+/// init that hands an outdated version to hook, e.g. [`SelfUpdate`] or [`OpenBrowser`].
+/// the session isnt initialized then, so `InitError::UpdateRequired` is still returned after the hook ran,
+/// or `InitError::Other` with the hook's error if it failed
+pub fn init_with_hook<C: KeyauthClient + ?Sized, H: UpdateHook>(client: &mut C, hash: Option<&str>, hook: &mut H) -> Result<(), InitError> {
+    match client.init(hash) {
+        Err(InitError::UpdateRequired(update)) => {
+            hook.update_required(&update)?;
+            Err(InitError::UpdateRequired(update))
+        }
+        other => other,
+    }
+}
+
+fn download_to(url: &str, path: &Path, sha256: Option<&str>) -> Result<(), String> {
+    let mut resp = Client::new().get(url)
+        .header("User-Agent", "KeyAuth")
+        .send()
+        .map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("download failed with status {}", resp.status()));
+    }
+    let mut file = fs::File::create(path).map_err(|e| e.to_string())?;
+    let mut hash = Hash::new();
+    let mut buf = [0u8; 16 * 1024];
+    loop {
+        let n = resp.read(&mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        hash.update(&buf[..n]);
+        file.write_all(&buf[..n]).map_err(|e| e.to_string())?;
+    }
+    file.sync_all().map_err(|e| e.to_string())?;
+    if let Some(sha256) = sha256 {
+        if !hex::encode(hash.finalize()).eq_ignore_ascii_case(sha256) {
+            return Err("downloaded update doesnt match the expected sha256".to_string());
+        }
+    }
+    Ok(())
+}
+
+fn current_exe() -> Result<PathBuf, String> {
+    std::env::current_exe().map_err(|e| e.to_string())
+}
+
+fn sibling(exe: &Path, extension: &str) -> PathBuf {
+    let mut name = exe.as_os_str().to_owned();
+    name.push(".");
+    name.push(extension);
+    PathBuf::from(name)
+}
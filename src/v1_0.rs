@@ -12,6 +12,7 @@ also if you want to use an obfuscator for rust i recommend using [obfstr](https:
 */
 
 use uuid::Uuid;
+use crate::{get_hwid, OnlineUser, ChatMessage, InitError, Secret, UpdateRequired};
 use std::collections::HashMap;
 use reqwest::blocking::Client;
 use hmac_sha256::HMAC;
@@ -29,10 +30,10 @@ type Aes256Cbc = Cbc<Aes256, Pkcs7>;
 pub struct KeyauthApi {
     name: String,
     owner_id: String,
-    secret: String,
+    secret: Secret,
     version: String,
-    enckey: String,
-    session_id: String,
+    enckey: Secret,
+    session_id: Secret,
     pub api_url: String,
     pub num_keys: String,
     pub num_online_users: String,
@@ -57,10 +58,10 @@ impl KeyauthApi {
         Self {
             name: name.to_string(),
             owner_id: owner_id.to_string(),
-            secret: secret.to_string(),
+            secret: secret.into(),
             version: version.to_string(),
-            enckey: String::new(),
-            session_id: String::new(),
+            enckey: Secret::default(),
+            session_id: Secret::default(),
             num_keys: String::new(),
             api_url: api_url.to_string(),
             num_online_users: String::new(),
@@ -69,7 +70,7 @@ impl KeyauthApi {
             customer_panel_link: String::new(),
             username: String::new(),
             ip: String::new(),
-            hwid: machine_uuid::get(),
+            hwid: get_hwid(),
             create_date: String::new(),
             last_login: String::new(),
             subscription: String::new(),
@@ -80,10 +81,11 @@ impl KeyauthApi {
         }
     }
 
-    /// initializes a session, **required to run before any other function in this struct!!!** accept new
-    pub fn init(&mut self, hash: Option<&str>) -> Result<(), String> {
+    /// initializes a session, **required to run before any other function in this struct!!!** accept new.
+    /// returns Err(InitError::UpdateRequired) if keyauth says this version is outdated
+    pub fn init(&mut self, hash: Option<&str>) -> Result<(), InitError> {
         let init_iv = Self::gen_init_iv();
-        self.enckey = Self::gen_init_iv();
+        self.enckey = Self::gen_init_iv().into();
 
         let mut req_data = HashMap::new();
         req_data.insert("type", encode_lower(b"init"));
@@ -102,7 +104,7 @@ impl KeyauthApi {
         let resp = Encryption::decrypt(resp, &self.secret, &init_iv);
         let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
         if json_rep["success"].as_bool().unwrap() {
-            self.session_id = json_rep["sessionid"].as_str().unwrap().to_string();
+            self.session_id = json_rep["sessionid"].as_str().unwrap().into();
             self.num_keys = json_rep["appinfo"]["numKeys"].as_str().unwrap().to_string();
             self.num_online_users = json_rep["appinfo"]["numOnlineUsers"].as_str().unwrap().to_string();
             self.num_users = json_rep["appinfo"]["numUsers"].as_str().unwrap().to_string();
@@ -110,12 +112,10 @@ impl KeyauthApi {
             Ok(())
         } else {
             if json_rep["message"].as_str().unwrap() == "invalidver" {
-                let download_url = json_rep["download"].as_str().unwrap();
-                if !download_url.is_empty() {
-                    webbrowser::open(download_url).unwrap();
-                }
+                let download_url = json_rep["download"].as_str().unwrap_or("").to_string();
+                return Err(InitError::UpdateRequired(UpdateRequired { download_url }));
             }
-            Err(json_rep["message"].as_str().unwrap().to_string())
+            Err(json_rep["message"].as_str().unwrap().to_string().into())
         }
     }
 
@@ -125,7 +125,7 @@ impl KeyauthApi {
 
         let hwidd = match hwid {
             Some(hwid) => hwid,
-            None => machine_uuid::get(),
+            None => get_hwid(),
         };
         let mut req_data = HashMap::new();
         req_data.insert("type", encode_lower(b"register"));
@@ -185,7 +185,7 @@ impl KeyauthApi {
         let init_iv = Self::gen_init_iv();
         let hwidd = match hwid {
             Some(hwid) => hwid,
-            None => machine_uuid::get(),
+            None => get_hwid(),
         };
         self.hwid = hwidd.clone();
 
@@ -223,7 +223,7 @@ impl KeyauthApi {
         let init_iv = Self::gen_init_iv();
         let hwidd = match hwid {
             Some(hwid) => hwid,
-            None => machine_uuid::get(),
+            None => get_hwid(),
         };
 
         let mut req_data = HashMap::new();
@@ -371,8 +371,13 @@ impl KeyauthApi {
         Ok(json_rep["success"].as_bool().unwrap())
     }
 
+    /// gets the list of online users
+    pub fn fetch_online(&mut self) -> Result<Vec<OnlineUser>, String> {
+        serde_json::from_value(self.fetch_online_raw()?).map_err(|e| e.to_string())
+    }
+
     /// gets json of online users
-    pub fn fetch_online(&mut self) -> Result<serde_json::Value, String> {
+    pub fn fetch_online_raw(&mut self) -> Result<serde_json::Value, String> {
         let init_iv = Self::gen_init_iv();
         let mut req_data = HashMap::new();
         req_data.insert("type", encode_lower(b"fetchOnline"));
@@ -394,8 +399,13 @@ impl KeyauthApi {
         }
     }
 
+    /// gets the messages in a channel
+    pub fn get_chat(&mut self, channel: String) -> Result<Vec<ChatMessage>, String> {
+        serde_json::from_value(self.get_chat_raw(channel)?).map_err(|e| e.to_string())
+    }
+
     /// gets the arry of messages in a channel
-    pub fn get_chat(&mut self, channel: String) -> Result<serde_json::Value, String> {
+    pub fn get_chat_raw(&mut self, channel: String) -> Result<serde_json::Value, String> {
         let init_iv = Self::gen_init_iv();
         let mut req_data = HashMap::new();
         req_data.insert("type", encode_lower(b"chatget"));
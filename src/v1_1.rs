@@ -3,6 +3,7 @@ WARNING! this api version has no special security, it should be only used if you
 */
 
 use uuid::Uuid;
+use crate::{get_hwid, OnlineUser, ChatMessage, InitError, Secret, UpdateRequired};
 use std::collections::HashMap;
 use reqwest::blocking::Client;
 use base16::decode;
@@ -11,9 +12,8 @@ use base16::decode;
 pub struct KeyauthApi {
     name: String,
     owner_id: String,
-    secret: String,
     version: String,
-    session_id: String,
+    session_id: Secret,
     pub api_url: String,
     pub num_keys: String,
     pub num_online_users: String,
@@ -34,13 +34,13 @@ pub struct KeyauthApi {
 
 impl KeyauthApi {
     /// creats a new KeyauthApi and its defaults, api_url has to be api version 1.2 example: "https://keyauth.win/api/1.2/" or if you have a custom api domain: "https://api.example.com/1.2/"
-    pub fn new(name: &str, owner_id: &str, secret: &str, version: &str, api_url: &str) -> Self {
+    /// the 1.1 api doesnt use the secret, it is only taken so every version has the same constructor
+    pub fn new(name: &str, owner_id: &str, _secret: &str, version: &str, api_url: &str) -> Self {
         Self {
             name: name.to_string(),
             owner_id: owner_id.to_string(),
-            secret: secret.to_string(),
             version: version.to_string(),
-            session_id: String::new(),
+            session_id: Secret::default(),
             num_keys: String::new(),
             api_url: api_url.to_string(),
             num_online_users: String::new(),
@@ -49,7 +49,7 @@ impl KeyauthApi {
             customer_panel_link: String::new(),
             username: String::new(),
             ip: String::new(),
-            hwid: machine_uuid::get(),
+            hwid: get_hwid(),
             create_date: String::new(),
             last_login: String::new(),
             subscription: String::new(),
@@ -60,8 +60,9 @@ impl KeyauthApi {
         }
     }
 
-    /// initializes a session, **required to run before any other function in this struct!!!** accept new
-    pub fn init(&mut self, hash: Option<&str>) -> Result<(), String> {
+    /// initializes a session, **required to run before any other function in this struct!!!** accept new.
+    /// returns Err(InitError::UpdateRequired) if keyauth says this version is outdated
+    pub fn init(&mut self, hash: Option<&str>) -> Result<(), InitError> {
         let mut req_data = HashMap::new();
         req_data.insert("type", "init");
         if hash.is_some() {
@@ -76,11 +77,11 @@ impl KeyauthApi {
         let resp = req.text().unwrap();
 
         if resp == "KeyAuth_Invalid" {
-            return Err("The application doesn't exist".to_string());
+            return Err("The application doesn't exist".to_string().into());
         }
         let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
         if json_rep["success"].as_bool().unwrap() {
-            self.session_id = json_rep["sessionid"].as_str().unwrap().to_string();
+            self.session_id = json_rep["sessionid"].as_str().unwrap().into();
             self.num_keys = json_rep["appinfo"]["numKeys"].as_str().unwrap().to_string();
             self.num_online_users = json_rep["appinfo"]["numOnlineUsers"].as_str().unwrap().to_string();
             self.num_users = json_rep["appinfo"]["numUsers"].as_str().unwrap().to_string();
@@ -88,12 +89,10 @@ impl KeyauthApi {
             Ok(())
         } else {
             if json_rep["message"].as_str().unwrap() == "invalidver" {
-                let download_url = json_rep["download"].as_str().unwrap();
-                if !download_url.is_empty() {
-                    webbrowser::open(download_url).unwrap();
-                }
+                let download_url = json_rep["download"].as_str().unwrap_or("").to_string();
+                return Err(InitError::UpdateRequired(UpdateRequired { download_url }));
             }
-            Err(json_rep["message"].as_str().unwrap().to_string())
+            Err(json_rep["message"].as_str().unwrap().to_string().into())
         }
     }
 
@@ -101,14 +100,14 @@ impl KeyauthApi {
     pub fn register(&mut self, username: String, password: String, license: String, hwid: Option<String>) -> Result<(), String> {
         let hwidd = match hwid {
             Some(hwid) => hwid,
-            None => machine_uuid::get(),
+            None => get_hwid(),
         };
         let mut req_data = HashMap::new();
         req_data.insert("type", "register");
         req_data.insert("username", &username);
         req_data.insert("pass", &password);
         req_data.insert("key", &license);
-        req_data.insert("sessionid", &self.session_id);
+        req_data.insert("sessionid", self.session_id.as_str());
         req_data.insert("name", &self.name);
         req_data.insert("ownerid", &self.owner_id);
         req_data.insert("hwid", &hwidd);
@@ -136,7 +135,7 @@ impl KeyauthApi {
         req_data.insert("type", "upgrade");
         req_data.insert("username", &username);
         req_data.insert("key", &license);
-        req_data.insert("sessionid", &self.session_id);
+        req_data.insert("sessionid", self.session_id.as_str());
         req_data.insert("name", &self.name);
         req_data.insert("ownerid", &self.owner_id);
 
@@ -156,7 +155,7 @@ impl KeyauthApi {
     pub fn login(&mut self, username: String, password: String, hwid: Option<String>) -> Result<(), String> {
         let hwidd = match hwid {
             Some(hwid) => hwid,
-            None => machine_uuid::get(),
+            None => get_hwid(),
         };
 
         let mut req_data = HashMap::new();
@@ -164,7 +163,7 @@ impl KeyauthApi {
         req_data.insert("username", &username);
         req_data.insert("pass", &password);
         req_data.insert("hwid", &hwidd);
-        req_data.insert("sessionid", &self.session_id);
+        req_data.insert("sessionid", self.session_id.as_str());
         req_data.insert("name", &self.name);
         req_data.insert("ownerid", &self.owner_id);
 
@@ -191,14 +190,14 @@ impl KeyauthApi {
     pub fn license(&mut self, license: String, hwid: Option<String>) -> Result<(), String> {
         let hwidd = match hwid {
             Some(hwid) => hwid,
-            None => machine_uuid::get(),
+            None => get_hwid(),
         };
 
         let mut req_data = HashMap::new();
         req_data.insert("type", "license");
         req_data.insert("key", &license);
         req_data.insert("hwid", &hwidd);
-        req_data.insert("sessionid", &self.session_id);
+        req_data.insert("sessionid", self.session_id.as_str());
         req_data.insert("name", &self.name);
         req_data.insert("ownerid", &self.owner_id);
 
@@ -226,7 +225,7 @@ impl KeyauthApi {
         let mut req_data = HashMap::new();
         req_data.insert("type", "var");
         req_data.insert("varid", &varid);
-        req_data.insert("sessionid", &self.session_id);
+        req_data.insert("sessionid", self.session_id.as_str());
         req_data.insert("name", &self.name);
         req_data.insert("ownerid", &self.owner_id);
 
@@ -248,7 +247,7 @@ impl KeyauthApi {
         let mut req_data = HashMap::new();
         req_data.insert("type", "file");
         req_data.insert("fileid", &fileid);
-        req_data.insert("sessionid", &self.session_id);
+        req_data.insert("sessionid", self.session_id.as_str());
         req_data.insert("name", &self.name);
         req_data.insert("ownerid", &self.owner_id);
 
@@ -271,7 +270,7 @@ impl KeyauthApi {
         req_data.insert("type", "webhook");
         req_data.insert("webid", &webid);
         req_data.insert("params", &params);
-        req_data.insert("sessionid", &self.session_id);
+        req_data.insert("sessionid", self.session_id.as_str());
         req_data.insert("name", &self.name);
         req_data.insert("ownerid", &self.owner_id);
 
@@ -292,7 +291,7 @@ impl KeyauthApi {
     pub fn checkblacklist(&mut self) -> Result<(), String> {
         let mut req_data = HashMap::new();
         req_data.insert("type", "checkblacklist");
-        req_data.insert("sessionid", &self.session_id);
+        req_data.insert("sessionid", self.session_id.as_str());
         req_data.insert("name", &self.name);
         req_data.insert("ownerid", &self.owner_id);
 
@@ -315,7 +314,7 @@ impl KeyauthApi {
     pub fn check_session(&mut self) -> Result<bool, String> {
         let mut req_data = HashMap::new();
         req_data.insert("type", "check");
-        req_data.insert("sessionid", &self.session_id);
+        req_data.insert("sessionid", self.session_id.as_str());
         req_data.insert("name", &self.name);
         req_data.insert("ownerid", &self.owner_id);
 
@@ -328,11 +327,16 @@ impl KeyauthApi {
         Ok(json_rep["success"].as_bool().unwrap())
     }
 
+    /// gets the list of online users
+    pub fn fetch_online(&mut self) -> Result<Vec<OnlineUser>, String> {
+        serde_json::from_value(self.fetch_online_raw()?).map_err(|e| e.to_string())
+    }
+
     /// gets json of online users
-    pub fn fetch_online(&mut self) -> Result<serde_json::Value, String> {
+    pub fn fetch_online_raw(&mut self) -> Result<serde_json::Value, String> {
         let mut req_data = HashMap::new();
         req_data.insert("type", "fetchOnline");
-        req_data.insert("sessionid", &self.session_id);
+        req_data.insert("sessionid", self.session_id.as_str());
         req_data.insert("name", &self.name);
         req_data.insert("ownerid", &self.owner_id);
 
@@ -349,12 +353,17 @@ impl KeyauthApi {
         }
     }
 
+    /// gets the messages in a channel
+    pub fn get_chat(&mut self, channel: String) -> Result<Vec<ChatMessage>, String> {
+        serde_json::from_value(self.get_chat_raw(channel)?).map_err(|e| e.to_string())
+    }
+
     /// gets the arry of messages in a channel
-    pub fn get_chat(&mut self, channel: String) -> Result<serde_json::Value, String> {
+    pub fn get_chat_raw(&mut self, channel: String) -> Result<serde_json::Value, String> {
         let mut req_data = HashMap::new();
         req_data.insert("type", "chatget");
         req_data.insert("channel", &channel);
-        req_data.insert("sessionid", &self.session_id);
+        req_data.insert("sessionid", self.session_id.as_str());
         req_data.insert("name", &self.name);
         req_data.insert("ownerid", &self.owner_id);
 
@@ -377,7 +386,7 @@ impl KeyauthApi {
         req_data.insert("type", "chatsend");
         req_data.insert("channel", &channel);
         req_data.insert("message", &message);
-        req_data.insert("sessionid", &self.session_id);
+        req_data.insert("sessionid", self.session_id.as_str());
         req_data.insert("name", &self.name);
         req_data.insert("ownerid", &self.owner_id);
 
@@ -398,7 +407,7 @@ impl KeyauthApi {
     pub fn ban(&mut self) {
         let mut req_data = HashMap::new();
         req_data.insert("type", "ban");
-        req_data.insert("sessionid", &self.session_id);
+        req_data.insert("sessionid", self.session_id.as_str());
         req_data.insert("name", &self.name);
         req_data.insert("ownerid", &self.owner_id);
 
@@ -411,7 +420,7 @@ impl KeyauthApi {
         req_data.insert("type", "setvar");
         req_data.insert("var", &varname);
         req_data.insert("data", &varvalue);
-        req_data.insert("sessionid", &self.session_id);
+        req_data.insert("sessionid", self.session_id.as_str());
         req_data.insert("name", &self.name);
         req_data.insert("ownerid", &self.owner_id);
 
@@ -431,7 +440,7 @@ impl KeyauthApi {
         let mut req_data = HashMap::new();
         req_data.insert("type", "getvar");
         req_data.insert("var", &varname);
-        req_data.insert("sessionid", &self.session_id);
+        req_data.insert("sessionid", self.session_id.as_str());
         req_data.insert("name", &self.name);
         req_data.insert("ownerid", &self.owner_id);
 
@@ -459,7 +468,7 @@ impl KeyauthApi {
         req_data.insert("type", "log");
         req_data.insert("message", &message);
         req_data.insert("pcuser", &usr);
-        req_data.insert("sessionid", &self.session_id);
+        req_data.insert("sessionid", self.session_id.as_str());
         req_data.insert("name", &self.name);
         req_data.insert("ownerid", &self.owner_id);
 
@@ -471,7 +480,7 @@ impl KeyauthApi {
         let mut req_data = HashMap::new();
         req_data.insert("type", "changeUsername");
         req_data.insert("newUsername", &new_username);
-        req_data.insert("sessionid", &self.session_id);
+        req_data.insert("sessionid", self.session_id.as_str());
         req_data.insert("name", &self.name);
         req_data.insert("ownerid", &self.owner_id);
 
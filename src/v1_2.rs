@@ -2,36 +2,46 @@
 unofficial [keyauth](https://keyauth.cc) library that uses 1.2 api version
 
 basic usage:
-```rust
+```rust,no_run
 let mut auth = keyauth::v1_2::KeyauthApi::new("application name", "ownerid", "application secret", "application version", "api url"); // if you dont have a custom domain for api use "https://keyauth.win/api/1.2/"
-auth.init().unwrap();
-auth.login("username", "password", Some("hwid".to_string()).unwrap()); // if you want to automaticly generate hwid use None insted.
+auth.init(None).unwrap();
+auth.login("username".to_string(), "password".to_string(), Some("hwid".to_string())).unwrap(); // if you want to automaticly generate hwid use None insted.
 ```
 
 also if you want to use an obfuscator for rust i recommend using [obfstr](https://crates.io/crates/obfstr) and [llvm obfuscator](https://github.com/eshard/obfuscator-llvm/wiki/Rust-obfuscation-guide)
 */
 
-use serde::Deserialize;
 use uuid::Uuid;
-use std::collections::HashMap;
 use reqwest::blocking::Client;
-use reqwest::blocking::Response;
-use hmac_sha256::HMAC;
-use base16::decode;
-use machineid_rs::{IdBuilder, Encryption};
-use machineid_rs::HWIDComponent;
+use hmac_sha256::{HMAC, Hash};
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use crate::{get_hwid, OnlineUser, ChatMessage, InitError, Sealed, Secret, UpdateRequired};
+use self::endpoint::*;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{BufWriter, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use zeroize::{Zeroize, Zeroizing};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::metrics::{ErrorKind, Metrics, Outcome, RequestEvent};
 
 
 /// every function in this struct (accept log) returns a Result and Err("Request was tampered with") will be returned if the request signature doesnt mathc the sha256 hmac of the message
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct KeyauthApi {
     name: String,
     owner_id: String,
-    secret: String,
+    /// only decrypted to sign and check requests
+    secret: Sealed,
     version: String,
-    enckey: String,
-    enckey_s: String,
-    session_id: String,
+    enckey: Secret,
+    enckey_s: Sealed,
+    session_id: Secret,
     pub api_url: String,
     pub num_keys: String,
     pub num_online_users: String,
@@ -50,28 +60,135 @@ pub struct KeyauthApi {
     pub success: bool,
     pub blacklisted: bool,
     pub response: String,
+    /// application info from the last successful init
+    pub app_info: Option<AppInfo>,
+    /// user info from the last successful register, login, license or web_login
+    pub user_info: Option<UserInfo>,
+    /// directory where downloaded files are kept by file id, see [`KeyauthApi::file_to_writer`].
+    /// a copy is only used when the download is asked for with its sha256
+    pub file_cache: Option<PathBuf>,
+    /// waits before every request and backs off when keyauth throttles, can be shared with other clients
+    pub rate_limiter: Option<crate::RateLimiter>,
+    /// gets an event for every request, e.g. [`InMemoryMetrics`](crate::metrics::InMemoryMetrics)
+    pub metrics: Option<Arc<dyn Metrics>>,
 }
 
-fn get_hwid() -> String {
-    let mut builder = IdBuilder::new(Encryption::SHA256);
-    builder
-        .add_component(HWIDComponent::SystemID)
-        .add_component(HWIDComponent::CPUCores);
+// the secret, encryption keys and session id only show whether they are set
+impl std::fmt::Debug for KeyauthApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyauthApi")
+            .field("name", &self.name)
+            .field("owner_id", &self.owner_id)
+            .field("secret", &self.secret)
+            .field("version", &self.version)
+            .field("enckey", &self.enckey)
+            .field("enckey_s", &self.enckey_s)
+            .field("session_id", &self.session_id)
+            .field("api_url", &self.api_url)
+            .field("username", &self.username)
+            .field("hwid", &self.hwid)
+            .field("subscription", &self.subscription)
+            .field("expiry", &self.expiry)
+            .field("message", &self.message)
+            .field("success", &self.success)
+            .field("app_info", &self.app_info)
+            .field("user_info", &self.user_info)
+            .field("file_cache", &self.file_cache)
+            .field("rate_limiter", &self.rate_limiter)
+            .field("metrics", &self.metrics)
+            .finish_non_exhaustive()
+    }
+}
+
+/// response of the init request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InitResponse {
+    pub sessionid: String,
+    pub appinfo: AppInfo,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppInfo {
+    pub num_users: String,
+    pub num_online_users: String,
+    pub num_keys: String,
+    pub version: String,
+    pub customer_panel_link: String,
+}
+
+/// response of register, login, license and web_login
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserResponse {
+    pub info: UserInfo,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserInfo {
+    pub username: String,
+    pub subscriptions: Vec<UserSubscription>,
+    pub ip: String,
+    pub hwid: Option<String>,
+    pub createdate: String,
+    pub lastlogin: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserSubscription {
+    pub subscription: String,
+    pub key: Option<String>,
+    pub expiry: String,
+    pub timeleft: i64,
+}
+
+/// response of requests that only return a message (var, webhook, change_username)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageResponse {
+    pub message: String,
+}
+
+/// response of the file request, contents are hex encoded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileResponse {
+    pub contents: String,
+}
+
+/// response of the getvar request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetVarResponse {
+    pub response: String,
+}
 
-    builder.build("mykey").unwrap()
+/// response of the fetchOnline request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnlineResponse {
+    pub users: serde_json::Value,
+}
+
+/// response of the chatget request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatResponse {
+    pub messages: serde_json::Value,
 }
 
 impl KeyauthApi {
     /// creats a new KeyauthApi and its defaults, api_url has to be api version 1.2 example: "https://keyauth.win/api/1.2/" or if you have a custom api domain: "https://api.example.com/1.2/"
     pub fn new(name: &str, owner_id: &str, secret: &str, version: &str, api_url: &str) -> Self {
+        Self::with_sealed_secret(name, owner_id, Sealed::new(secret), version, api_url)
+    }
+
+    /// same as new with an already encrypted secret, e.g. from [`sealed!`](crate::sealed). [`app!`](crate::app) calls this
+    pub fn with_sealed_secret(name: &str, owner_id: &str, secret: Sealed, version: &str, api_url: &str) -> Self {
         Self {
             name: name.to_string(),
             owner_id: owner_id.to_string(),
-            secret: secret.to_string(),
+            secret,
             version: version.to_string(),
-            enckey: String::new(),
-            enckey_s: String::new(),
-            session_id: String::new(),
+            enckey: Secret::default(),
+            enckey_s: Sealed::default(),
+            session_id: Secret::default(),
             num_keys: String::new(),
             api_url: api_url.to_string(),
             num_online_users: String::new(),
@@ -90,75 +207,35 @@ impl KeyauthApi {
             success: false,
             blacklisted: false,
             response: String::new(),
+            app_info: None,
+            user_info: None,
+            file_cache: None,
+            rate_limiter: None,
+            metrics: None,
         }
     }
 
-    /// initializes a session, **required to run before any other function in this struct!!!** accept new
-    pub fn init(&mut self, hash: Option<&str>) -> Result<(), String> {
-        self.enckey = Uuid::new_v4().simple().to_string();
-        self.enckey_s = format!("{}-{}", self.enckey, self.secret);
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "init");
-        if hash.is_some() {
-            req_data.insert("hash", hash.unwrap());
-        }
-        req_data.insert("ver", &self.version);
-        req_data.insert("name", &self.name);
-        req_data.insert("ownerid", &self.owner_id);
-        req_data.insert("enckey", &self.enckey);
-
-        let req = Self::request(req_data, &self.api_url);
-        let head = req.headers().clone();
-        let resp = req.text().unwrap();
-
-        if resp == "KeyAuth_Invalid" {
-            return Err("The application doesn't exist".to_string());
-        }
-        if !head.contains_key("signature") {
-            #[cfg(feature = "panic")]
-            {
-                panic!("response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("response was tampered with".to_string());
-            }
-            #[cfg(feature = "panic")]
-            {
-                panic!("response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("response was tampered with".to_string());
-            }
-        }
-        let sig = head.get("signature").unwrap().to_str().unwrap();
-        if sig != Self::make_hmac(&resp, &self.secret) {
-            #[cfg(feature = "panic")]
-            {
-                panic!("response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("response was tampered with".to_string());
-            }
-        }
-        let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
-        if json_rep["success"].as_bool().unwrap() {
-            self.session_id = json_rep["sessionid"].as_str().unwrap().to_string();
-            self.num_keys = json_rep["appinfo"]["numKeys"].as_str().unwrap().to_string();
-            self.num_online_users = json_rep["appinfo"]["numOnlineUsers"].as_str().unwrap().to_string();
-            self.num_users = json_rep["appinfo"]["numUsers"].as_str().unwrap().to_string();
-            self.customer_panel_link = json_rep["appinfo"]["customerPanelLink"].as_str().unwrap_or("").to_string();
+    /// initializes a session, **required to run before any other function in this struct!!!** accept new.
+    /// returns Err(InitError::UpdateRequired) if keyauth says this version is outdated
+    pub fn init(&mut self, hash: Option<&str>) -> Result<(), InitError> {
+        self.enckey = Uuid::new_v4().simple().to_string().into();
+        self.enckey_s = Sealed::new(&Zeroizing::new(format!("{}-{}", self.enckey.as_str(), self.secret.reveal().as_str())));
+        let (reply, body) = self.exchange(&Init { ver: &self.version, hash, enckey: &self.enckey })?;
+        if reply.success {
+            let resp: InitResponse = parse(&body)?;
+            self.session_id = resp.sessionid.into();
+            self.num_keys = resp.appinfo.num_keys.clone();
+            self.num_online_users = resp.appinfo.num_online_users.clone();
+            self.num_users = resp.appinfo.num_users.clone();
+            self.customer_panel_link = resp.appinfo.customer_panel_link.clone();
+            self.app_info = Some(resp.appinfo);
             Ok(())
         } else {
-            if json_rep["message"].as_str().unwrap() == "invalidver" {
-                let download_url = json_rep["download"].as_str().unwrap();
-                if !download_url.is_empty() {
-                    webbrowser::open(download_url).unwrap();
-                }
+            if reply.message == "invalidver" {
+                let download_url = reply.download.unwrap_or_default();
+                return Err(InitError::UpdateRequired(UpdateRequired { download_url }));
             }
-            Err(json_rep["message"].as_str().unwrap().to_string())
+            Err(reply.message.into())
         }
     }
 
@@ -168,97 +245,16 @@ impl KeyauthApi {
             Some(hwid) => hwid,
             None => get_hwid(),
         };
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "register");
-        req_data.insert("username", &username);
-        req_data.insert("pass", &password);
-        req_data.insert("key", &license);
-        req_data.insert("sessionid", &self.session_id);
-        req_data.insert("name", &self.name);
-        req_data.insert("ownerid", &self.owner_id);
-        req_data.insert("hwid", &hwidd);
-
-        let req = Self::request(req_data, &self.api_url);
-        let head = req.headers().clone();
-        let resp = req.text().unwrap();
-
-        if !head.contains_key("signature") {
-            #[cfg(feature = "panic")]
-            {
-                panic!("response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("response was tampered with".to_string());
-            }
-        }
-        let sig = head.get("signature").unwrap().to_str().unwrap();
-        if sig != Self::make_hmac(&resp, &self.enckey_s) {
-            #[cfg(feature = "panic")]
-            {
-                panic!("Response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("Response was tampered with".to_string());
-            }
-        }
-        let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
-        if json_rep["success"].as_bool().unwrap() {
-            self.username = username;
-            self.ip = json_rep["info"]["ip"].as_str().unwrap().to_string();
-            self.create_date = json_rep["info"]["createdate"].as_str().unwrap().to_string();
-            self.last_login = json_rep["info"]["lastlogin"].as_str().unwrap().to_string();
-            self.subscription = json_rep["info"]["subscriptions"][0]["subscription"].as_str().unwrap().to_string();
-            self.sub_time_left = json_rep["info"]["subscriptions"][0]["timeleft"].as_i64().unwrap();
-            self.expiry = json_rep["info"]["subscriptions"][0]["expiry"].as_str().unwrap().to_string();
-            Ok(())
-        } else {
-            Err(json_rep["message"].as_str().unwrap().to_string())
-        }
+        let resp = self.call(&Register { username: &username, pass: &password, key: &license, hwid: &hwidd })?;
+        self.username = username;
+        self.set_user_info(resp.info);
+        Ok(())
     }
 
     /// upgrades a user license level or extends a license
     pub fn upgrade(&mut self, username: String, license: String) -> Result<(), String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "upgrade");
-        req_data.insert("username", &username);
-        req_data.insert("key", &license);
-        req_data.insert("sessionid", &self.session_id);
-        req_data.insert("name", &self.name);
-        req_data.insert("ownerid", &self.owner_id);
-
-        let req = Self::request(req_data, &self.api_url);
-        let head = req.headers().clone();
-        let resp = req.text().unwrap();
-
-        if !head.contains_key("signature") {
-            #[cfg(feature = "panic")]
-            {
-                panic!("response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("response was tampered with".to_string());
-            }
-        }
-        let sig = head.get("signature").unwrap().to_str().unwrap();
-        if sig != Self::make_hmac(&resp, &self.enckey_s) {
-            #[cfg(feature = "panic")]
-            {
-                panic!("Response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("Response was tampered with".to_string());
-            }
-        }
-        let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
-        if json_rep["success"].as_bool().unwrap() {
-            Ok(())
-        } else {
-            Err(json_rep["message"].as_str().unwrap().to_string())
-        }
+        self.call(&Upgrade { username: &username, key: &license })?;
+        Ok(())
     }
 
     /// login self explanatory
@@ -267,56 +263,11 @@ impl KeyauthApi {
             Some(hwid) => hwid,
             None => get_hwid(),
         };
-
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "login");
-        req_data.insert("username", &username);
-        req_data.insert("pass", &password);
-        req_data.insert("hwid", &hwidd);
-        req_data.insert("sessionid", &self.session_id);
-        req_data.insert("name", &self.name);
-        req_data.insert("ownerid", &self.owner_id);
-
-        let req = Self::request(req_data, &self.api_url);
-        let head = req.headers().clone();
-        let resp = req.text().unwrap();
-
-        if !head.contains_key("signature") {
-            #[cfg(feature = "panic")]
-            {
-                panic!("response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("response was tampered with".to_string());
-            }
-        }
-        let sig = head.get("signature").unwrap().to_str().unwrap();
-        if sig != Self::make_hmac(&resp, &self.enckey_s) {
-            #[cfg(feature = "panic")]
-            {
-                panic!("Response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("Response was tampered with".to_string());
-            }
-        }
-        let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
-
-        if json_rep["success"].as_bool().unwrap() {
-            self.username = username;
-            self.ip = json_rep["info"]["ip"].as_str().unwrap().to_string();
-            self.hwid = Some(hwidd);
-            self.create_date = json_rep["info"]["createdate"].as_str().unwrap().to_string();
-            self.last_login = json_rep["info"]["lastlogin"].as_str().unwrap().to_string();
-            self.subscription = json_rep["info"]["subscriptions"][0]["subscription"].as_str().unwrap().to_string();
-            self.sub_time_left = json_rep["info"]["subscriptions"][0]["timeleft"].as_i64().unwrap();
-            self.expiry = json_rep["info"]["subscriptions"][0]["expiry"].as_str().unwrap().to_string();
-            Ok(())
-        } else {
-            Err(json_rep["message"].as_str().unwrap().to_string())
-        }
+        let resp = self.call(&Login { username: &username, pass: &password, hwid: &hwidd })?;
+        self.username = username;
+        self.hwid = Some(hwidd);
+        self.set_user_info(resp.info);
+        Ok(())
     }
 
     /// <https://docs.keyauth.cc/api/license>
@@ -325,492 +276,239 @@ impl KeyauthApi {
             Some(hwid) => hwid,
             None => get_hwid(),
         };
+        let resp = self.call(&License { key: &license, hwid: &hwidd })?;
+        self.username = resp.info.username.clone();
+        self.hwid = Some(hwidd);
+        self.set_user_info(resp.info);
+        Ok(())
+    }
 
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "license");
-        req_data.insert("key", &license);
-        req_data.insert("hwid", &hwidd);
-        req_data.insert("sessionid", &self.session_id);
-        req_data.insert("name", &self.name);
-        req_data.insert("ownerid", &self.owner_id);
-
-        let req = Self::request(req_data, &self.api_url);
-        let head = req.headers().clone();
-        let resp = req.text().unwrap();
-
-        if !head.contains_key("signature") {
-            #[cfg(feature = "panic")]
-            {
-                panic!("response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("response was tampered with".to_string());
-            }
+    /// this will get a global variable (not user) and return it
+    pub fn var(&mut self, varid: String) -> Result<String, String> {
+        Ok(self.call(&Var { varid: &varid })?.message)
+    }
+
+    /// downloads a file, and decodes using base16::decode
+    pub fn file(&mut self, fileid: String) -> Result<Vec<u8>, String> {
+        let mut contents = Vec::new();
+        self.file_to_writer(fileid, &mut contents, None, None)?;
+        Ok(contents)
+    }
+
+    /// downloads a file and decodes it into writer while it is downloading, returns the number of bytes written.
+    /// the signature and sha256 can only be checked after the whole file was downloaded, so on Err the writer can already contain data that has to be thrown away.
+    /// sha256 is the expected hex sha256 of the decoded file, if self.file_cache has a copy with that hash the file isnt downloaded again.
+    /// without sha256 there is no way to tell if the copy is still the right file, so it is always downloaded (and the copy updated).
+    /// progress gets called with the downloaded and total (if known) response size in bytes
+    pub fn file_to_writer<W: Write>(&mut self, fileid: String, mut writer: W, sha256: Option<&str>, progress: Option<&mut dyn FnMut(u64, Option<u64>)>) -> Result<u64, String> {
+        let cached = self.file_cache.as_ref().map(|dir| dir.join(cache_name(&fileid)));
+        if let Some(file) = cached.as_deref().zip(sha256).and_then(|(cached, sha256)| cached_copy(cached, sha256)) {
+            return copy_zeroized(file, &mut writer, |_| ()).map_err(|e| e.to_string());
         }
-        let sig = head.get("signature").unwrap().to_str().unwrap();
-        if sig != Self::make_hmac(&resp, &self.enckey_s) {
-            #[cfg(feature = "panic")]
-            {
-                panic!("Response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("Response was tampered with".to_string());
+        self.download_file(&fileid, &mut writer, sha256, progress, cached.as_deref())
+    }
+
+    /// downloads a file into an anonymous in memory file (memfd) so the decoded file never touches the disk, the file cache isnt used.
+    /// the returned [`MemFile::path`] can be executed or loaded like a normal file while the MemFile is alive
+    #[cfg(target_os = "linux")]
+    pub fn file_to_memfd(&mut self, fileid: String, name: &str, sha256: Option<&str>) -> Result<MemFile, String> {
+        let mem = MemFile::new(name)?;
+        let mut writer = &mem.file;
+        self.download_file(&fileid, &mut writer, sha256, None, None)?;
+        Ok(mem)
+    }
+
+    /// streams the file response into writer and cache (if set), the decoded scratch buffers are zeroed afterwards
+    fn download_file(&self, fileid: &str, writer: &mut dyn Write, sha256: Option<&str>, progress: Option<&mut dyn FnMut(u64, Option<u64>)>, cached: Option<&Path>) -> Result<u64, String> {
+        let start = Instant::now();
+        let endpoint = File { fileid };
+        let mut call = crate::trace::Call::start("1.2", File::TYPE, &endpoint);
+        let result = self.verified_download(&endpoint, &call, writer, sha256, progress, cached);
+        let outcome = match &result {
+            Ok(_) => {
+                call.success(true);
+                Outcome::Success
+            }
+            Err((outcome, message)) => {
+                call.error(message);
+                *outcome
             }
+        };
+        self.record(File::TYPE, start, outcome);
+        if let (Err(_), Some(cached)) = (&result, cached) {
+            let _ = fs::remove_file(part_path(cached));
         }
-        let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
-
-        if json_rep["success"].as_bool().unwrap() {
-            self.username = json_rep["info"]["username"].as_str().unwrap().to_string();
-            self.ip = json_rep["info"]["ip"].as_str().unwrap().to_string();
-            self.hwid = Some(hwidd);
-            self.create_date = json_rep["info"]["createdate"].as_str().unwrap().to_string();
-            self.last_login = json_rep["info"]["lastlogin"].as_str().unwrap().to_string();
-            self.subscription = json_rep["info"]["subscriptions"][0]["subscription"].as_str().unwrap().to_string();
-            self.sub_time_left = json_rep["info"]["subscriptions"][0]["timeleft"].as_i64().unwrap();
-            self.expiry = json_rep["info"]["subscriptions"][0]["expiry"].as_str().unwrap().to_string();
-            Ok(())
-        } else {
-            Err(json_rep["message"].as_str().unwrap().to_string())
-        }
+        // counted first, tampered panics with the panic feature
+        result.map_err(|(outcome, message)| match outcome {
+            Outcome::Error(ErrorKind::Tampered) => tampered(&message),
+            _ => message,
+        })
     }
 
-    /// this will get a global variable (not user) and return it
-    pub fn var(&mut self, varid: String) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "var");
-        req_data.insert("varid", &varid);
-        req_data.insert("sessionid", &self.session_id);
-        req_data.insert("name", &self.name);
-        req_data.insert("ownerid", &self.owner_id);
-
-        let req = Self::request(req_data, &self.api_url);
-        let head = req.headers().clone();
-        let resp = req.text().unwrap();
-
-        if !head.contains_key("signature") {
-            #[cfg(feature = "panic")]
-            {
-                panic!("response was tampered with");
+    /// the file download, the error says how it is counted in the metrics
+    fn verified_download(&self, endpoint: &File, call: &crate::trace::Call, writer: &mut dyn Write, sha256: Option<&str>, mut progress: Option<&mut dyn FnMut(u64, Option<u64>)>, cached: Option<&Path>) -> Result<u64, (Outcome, String)> {
+        let network = |e: std::io::Error| (Outcome::Error(ErrorKind::Network), e.to_string());
+        let mut resp = self.post(endpoint, call).map_err(|e| (Outcome::Error(ErrorKind::Network), e))?;
+        let sig = match resp.headers().get("signature").and_then(|s| s.to_str().ok()) {
+            Some(sig) => sig.to_string(),
+            None => {
+                call.signature("missing");
+                return Err((Outcome::Error(ErrorKind::Tampered), "response was tampered with".to_string()));
             }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("response was tampered with".to_string());
+        };
+        let total = resp.content_length();
+        let mut cache = match cached {
+            Some(cached) => {
+                if let Some(dir) = cached.parent() {
+                    fs::create_dir_all(dir).map_err(network)?;
+                }
+                Some(fs::File::create(part_path(cached)).map_err(network)?)
             }
-        }
-        let sig = head.get("signature").unwrap().to_str().unwrap();
-        if sig != Self::make_hmac(&resp, &self.enckey_s) {
-            #[cfg(feature = "panic")]
-            {
-                panic!("Response was tampered with");
+            None => None,
+        };
+
+        let mut hmac = HMAC::new(self.enckey_s.reveal().as_bytes());
+        let mut hash = Hash::new();
+        let mut scanner = FileScanner::default();
+        let mut decoded = Zeroizing::new(Vec::new());
+        let mut written = 0u64;
+        let mut downloaded = 0u64;
+        let mut buf = Zeroizing::new([0u8; 16 * 1024]);
+        let invalid = |message: String| (Outcome::Error(ErrorKind::InvalidResponse), message);
+        loop {
+            let n = resp.read(&mut buf[..]).map_err(network)?;
+            if n == 0 {
+                break;
+            }
+            hmac.update(&buf[..n]);
+            decoded.zeroize();
+            scanner.feed(&buf[..n], &mut decoded).map_err(invalid)?;
+            if !decoded.is_empty() {
+                hash.update(&decoded[..]);
+                writer.write_all(&decoded).map_err(network)?;
+                if let Some(cache) = &mut cache {
+                    cache.write_all(&decoded).map_err(network)?;
+                }
+                written += decoded.len() as u64;
             }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("Response was tampered with".to_string());
+            downloaded += n as u64;
+            if let Some(progress) = &mut progress {
+                progress(downloaded, total);
             }
         }
-        let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
+        decoded.zeroize();
+        writer.flush().map_err(network)?;
 
-        if json_rep["success"].as_bool().unwrap() {
-            Ok(json_rep["message"].as_str().unwrap().to_string())
-        } else {
-            Err(json_rep["message"].as_str().unwrap().to_string())
+        if scanner.skeleton == b"KeyAuth_Invalid" {
+            return Err((Outcome::Error(ErrorKind::InvalidApplication), "The application doesn't exist".to_string()));
         }
-    }
-
-    /// downloads a file, and decodes using base16::decode
-    pub fn file(&mut self, fileid: String) -> Result<Vec<u8>, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "file");
-        req_data.insert("fileid", &fileid);
-        req_data.insert("sessionid", &self.session_id);
-        req_data.insert("name", &self.name);
-        req_data.insert("ownerid", &self.owner_id);
-
-        let req = Self::request(req_data, &self.api_url);
-        let head = req.headers().clone();
-        let resp = req.text().unwrap();
-
-        if !head.contains_key("signature") {
-            #[cfg(feature = "panic")]
-            {
-                panic!("response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("response was tampered with".to_string());
-            }
+        if sig != hex::encode(hmac.finalize()) {
+            call.signature("invalid");
+            return Err((Outcome::Error(ErrorKind::Tampered), "Response was tampered with".to_string()));
         }
-        let sig = head.get("signature").unwrap().to_str().unwrap();
-        if sig != Self::make_hmac(&resp, &self.enckey_s) {
-            #[cfg(feature = "panic")]
-            {
-                panic!("Response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("Response was tampered with".to_string());
+        call.signature("valid");
+        let reply: Reply = serde_json::from_slice(&scanner.skeleton).map_err(|e| invalid(e.to_string()))?;
+        if !reply.success {
+            return Err((Outcome::Failed, reply.message));
+        }
+        if scanner.nibble.is_some() {
+            return Err(invalid("file contents have an odd number of hex digits".to_string()));
+        }
+        if let Some(sha256) = sha256 {
+            if !hex::encode(hash.finalize()).eq_ignore_ascii_case(sha256) {
+                return Err(invalid("file doesnt match the expected sha256".to_string()));
             }
         }
-        let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
-
-        if json_rep["success"].as_bool().unwrap() {
-            Ok(decode(json_rep["contents"].as_str().unwrap()).unwrap())
-        } else {
-            Err(json_rep["message"].as_str().unwrap().to_string())
+        if let (Some(cached), Some(cache)) = (cached, cache) {
+            drop(cache);
+            fs::rename(part_path(cached), cached).map_err(network)?;
         }
+        Ok(written)
     }
 
-    /// sends a webhook from keyauth's servers so the url isnt exposed
-    pub fn webhook(&mut self, webid: String, params: String) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "webhook");
-        req_data.insert("webid", &webid);
-        req_data.insert("params", &params);
-        req_data.insert("sessionid", &self.session_id);
-        req_data.insert("name", &self.name);
-        req_data.insert("ownerid", &self.owner_id);
-
-        let req = Self::request(req_data, &self.api_url);
-        let head = req.headers().clone();
-        let resp = req.text().unwrap();
-
-        if !head.contains_key("signature") {
-            #[cfg(feature = "panic")]
-            {
-                panic!("response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("response was tampered with".to_string());
+    /// same as [`KeyauthApi::file_to_writer`] but writes to a file, the file is only created once the download was verified
+    pub fn file_to_path<P: AsRef<Path>>(&mut self, fileid: String, path: P, sha256: Option<&str>, progress: Option<&mut dyn FnMut(u64, Option<u64>)>) -> Result<u64, String> {
+        let path = path.as_ref();
+        let part = part_path(path);
+        let file = fs::File::create(&part).map_err(|e| e.to_string())?;
+        match self.file_to_writer(fileid, BufWriter::new(file), sha256, progress) {
+            Ok(written) => {
+                fs::rename(&part, path).map_err(|e| e.to_string())?;
+                Ok(written)
             }
-        }
-        let sig = head.get("signature").unwrap().to_str().unwrap();
-        if sig != Self::make_hmac(&resp, &self.enckey_s) {
-            #[cfg(feature = "panic")]
-            {
-                panic!("Response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("Response was tampered with".to_string());
+            Err(e) => {
+                let _ = fs::remove_file(&part);
+                Err(e)
             }
         }
-        let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
+    }
 
-        if json_rep["success"].as_bool().unwrap() {
-            Ok(json_rep["message"].as_str().unwrap().to_string())
-        } else {
-            Err(json_rep["message"].as_str().unwrap().to_string())
-        }
+    /// sends a webhook from keyauth's servers so the url isnt exposed
+    pub fn webhook(&mut self, webid: String, params: String) -> Result<String, String> {
+        Ok(self.call(&Webhook { webid: &webid, params: &params })?.message)
     }
 
     /// checks if the user is blacklisted and sets self.blacklisted acordingly
     pub fn checkblacklist(&mut self) -> Result<(), String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "checkblacklist");
-        req_data.insert("sessionid", &self.session_id);
-        req_data.insert("name", &self.name);
-        req_data.insert("ownerid", &self.owner_id);
-
-        let req = Self::request(req_data, &self.api_url);
-        let head = req.headers().clone();
-        let resp = req.text().unwrap();
-
-        if !head.contains_key("signature") {
-            #[cfg(feature = "panic")]
-            {
-                panic!("response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("response was tampered with".to_string());
-            }
-        }
-        let sig = head.get("signature").unwrap().to_str().unwrap();
-        if sig != Self::make_hmac(&resp, &self.enckey_s) {
-            #[cfg(feature = "panic")]
-            {
-                panic!("Response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("Response was tampered with".to_string());
-            }
-        }
-        let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
-
-        if json_rep["success"].as_bool().unwrap() {
-            self.blacklisted = true;
-            Ok(())
-        } else {
-            self.blacklisted = false;
-            Ok(())
-        }
+        let (reply, _) = self.exchange(&CheckBlacklist {})?;
+        self.blacklisted = reply.success;
+        Ok(())
     }
 
     /// checks if the session is still active or if it expired
     pub fn check_session(&mut self) -> Result<bool, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "check");
-        req_data.insert("sessionid", &self.session_id);
-        req_data.insert("name", &self.name);
-        req_data.insert("ownerid", &self.owner_id);
-
-        let req = Self::request(req_data, &self.api_url);
-        let head = req.headers().clone();
-        let resp = req.text().unwrap();
-
-        if !head.contains_key("signature") {
-            #[cfg(feature = "panic")]
-            {
-                panic!("response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("response was tampered with".to_string());
-            }
-        }
-        let sig = head.get("signature").unwrap().to_str().unwrap();
-        if sig != Self::make_hmac(&resp, &self.enckey_s) {
-            #[cfg(feature = "panic")]
-            {
-                panic!("Response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("Response was tampered with".to_string());
-            }
-        }
-        let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
+        let (reply, _) = self.exchange(&Check {})?;
+        Ok(reply.success)
+    }
 
-        Ok(json_rep["success"].as_bool().unwrap())
+    /// gets the list of online users
+    pub fn fetch_online(&mut self) -> Result<Vec<OnlineUser>, String> {
+        serde_json::from_value(self.fetch_online_raw()?).map_err(|e| e.to_string())
     }
 
     /// gets json of online users
-    pub fn fetch_online(&mut self) -> Result<serde_json::Value, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "fetchOnline");
-        req_data.insert("sessionid", &self.session_id);
-        req_data.insert("name", &self.name);
-        req_data.insert("ownerid", &self.owner_id);
-
-        let req = Self::request(req_data, &self.api_url);
-        let head = req.headers().clone();
-        let resp = req.text().unwrap();
-
-        if !head.contains_key("signature") {
-            #[cfg(feature = "panic")]
-            {
-                panic!("response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("response was tampered with".to_string());
-            }
-        }
-        let sig = head.get("signature").unwrap().to_str().unwrap();
-        if sig != Self::make_hmac(&resp, &self.enckey_s) {
-            #[cfg(feature = "panic")]
-            {
-                panic!("Response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("Response was tampered with".to_string());
-            }
-        }
-        let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
+    pub fn fetch_online_raw(&mut self) -> Result<serde_json::Value, String> {
+        Ok(self.call(&FetchOnline {})?.users)
+    }
 
-        if json_rep["success"].as_bool().unwrap() {
-            Ok(json_rep["users"].clone())
-        } else {
-            Err(json_rep["message"].as_str().unwrap().to_string())
-        }
+    /// gets the messages in a channel
+    pub fn get_chat(&mut self, channel: String) -> Result<Vec<ChatMessage>, String> {
+        serde_json::from_value(self.get_chat_raw(channel)?).map_err(|e| e.to_string())
     }
 
     /// gets the arry of messages in a channel
-    pub fn get_chat(&mut self, channel: String) -> Result<serde_json::Value, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "chatget");
-        req_data.insert("channel", &channel);
-        req_data.insert("sessionid", &self.session_id);
-        req_data.insert("name", &self.name);
-        req_data.insert("ownerid", &self.owner_id);
-
-        let req = Self::request(req_data, &self.api_url);
-        let head = req.headers().clone();
-        let resp = req.text().unwrap();
-
-        if !head.contains_key("signature") {
-            #[cfg(feature = "panic")]
-            {
-                panic!("response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("response was tampered with".to_string());
-            }
-        }
-        let sig = head.get("signature").unwrap().to_str().unwrap();
-        if sig != Self::make_hmac(&resp, &self.enckey_s) {
-            #[cfg(feature = "panic")]
-            {
-                panic!("Response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("Response was tampered with".to_string());
-            }
-        }
-        let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
-
-        if json_rep["success"].as_bool().unwrap() {
-            Ok(json_rep["messages"].clone())
-        } else {
-            Err(json_rep["message"].as_str().unwrap().to_string())
-        }
+    pub fn get_chat_raw(&mut self, channel: String) -> Result<serde_json::Value, String> {
+        Ok(self.call(&ChatGet { channel: &channel })?.messages)
     }
 
     /// sends a chat message in a channel
     pub fn send_chat_message(&mut self, channel: String, message: String) -> Result<(), String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "chatsend");
-        req_data.insert("channel", &channel);
-        req_data.insert("message", &message);
-        req_data.insert("sessionid", &self.session_id);
-        req_data.insert("name", &self.name);
-        req_data.insert("ownerid", &self.owner_id);
-
-        let req = Self::request(req_data, &self.api_url);
-        let head = req.headers().clone();
-        let resp = req.text().unwrap();
-
-        if !head.contains_key("signature") {
-            #[cfg(feature = "panic")]
-            {
-                panic!("response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("response was tampered with".to_string());
-            }
-        }
-        let sig = head.get("signature").unwrap().to_str().unwrap();
-        if sig != Self::make_hmac(&resp, &self.enckey_s) {
-            #[cfg(feature = "panic")]
-            {
-                panic!("Response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("Response was tampered with".to_string());
-            }
-        }
-        let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
+        self.call(&ChatSend { channel: &channel, message: &message })?;
+        Ok(())
+    }
 
-        if json_rep["success"].as_bool().unwrap() {
-            Ok(())
-        } else {
-            Err(json_rep["message"].as_str().unwrap().to_string())
-        }
+    /// opens a [`ChatChannel`] that polls `channel` every `poll_interval` in the background.
+    /// delay is the channel delay set with seller::chat::create_channel, sending faster than that is refused locally
+    pub fn chat_channel(&self, channel: &str, delay: Duration, poll_interval: Duration) -> ChatChannel {
+        ChatChannel::new(self.clone(), channel, delay, poll_interval)
     }
 
     /// self explanatory
     pub fn ban(&mut self) {
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "ban");
-        req_data.insert("sessionid", &self.session_id);
-        req_data.insert("name", &self.name);
-        req_data.insert("ownerid", &self.owner_id);
-
-        Self::request(req_data, &self.api_url);
+        self.fire(&Ban {});
     }
 
     /// sets a user variable to varvalue
     pub fn setvar(&mut self, varname: String, varvalue: String) -> Result<(), String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "setvar");
-        req_data.insert("var", &varname);
-        req_data.insert("data", &varvalue);
-        req_data.insert("sessionid", &self.session_id);
-        req_data.insert("name", &self.name);
-        req_data.insert("ownerid", &self.owner_id);
-
-        let req = Self::request(req_data, &self.api_url);
-        let head = req.headers().clone();
-        let resp = req.text().unwrap();
-
-        if !head.contains_key("signature") {
-            #[cfg(feature = "panic")]
-            {
-                panic!("response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("response was tampered with".to_string());
-            }
-        }
-        let sig = head.get("signature").unwrap().to_str().unwrap();
-        if sig != Self::make_hmac(&resp, &self.enckey_s) {
-            #[cfg(feature = "panic")]
-            {
-                panic!("Response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("Response was tampered with".to_string());
-            }
-        }
-        let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
-
-        self.message = json_rep["message"].as_str().unwrap().to_string();
-        self.success = json_rep["success"].as_bool().unwrap();
+        let (reply, _) = self.exchange(&SetVar { var: &varname, data: &varvalue })?;
+        self.message = reply.message;
+        self.success = reply.success;
         Ok(())
     }
 
     /// gets a user variable
     pub fn getvar(&mut self, varname: String) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "getvar");
-        req_data.insert("var", &varname);
-        req_data.insert("sessionid", &self.session_id);
-        req_data.insert("name", &self.name);
-        req_data.insert("ownerid", &self.owner_id);
-
-        let req = Self::request(req_data, &self.api_url);
-        let head = req.headers().clone();
-        let resp = req.text().unwrap();
-
-        if !head.contains_key("signature") {
-            #[cfg(feature = "panic")]
-            {
-                panic!("response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("response was tampered with".to_string());
-            }
-        }
-        let sig = head.get("signature").unwrap().to_str().unwrap();
-        if sig != Self::make_hmac(&resp, &self.enckey_s) {
-            #[cfg(feature = "panic")]
-            {
-                panic!("Response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("Response was tampered with".to_string());
-            }
-        }
-        let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
-
-        if json_rep["success"].as_bool().unwrap() {
-            Ok(json_rep["response"].as_str().unwrap().to_string())
-        } else {
-            Err(json_rep["message"].as_str().unwrap().to_string())
-        }
+        Ok(self.call(&GetVar { var: &varname })?.response)
     }
 
     /// logs somethink to keyauth
@@ -819,68 +517,22 @@ impl KeyauthApi {
             Some(pcuser) => pcuser,
             None => self.username.clone(),
         };
-
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "log");
-        req_data.insert("message", &message);
-        req_data.insert("pcuser", &usr);
-        req_data.insert("sessionid", &self.session_id);
-        req_data.insert("name", &self.name);
-        req_data.insert("ownerid", &self.owner_id);
-
-        Self::request(req_data, &self.api_url);
+        self.fire(&Log { message: &message, pcuser: &usr });
     }
 
-    /// changes Username, 
+    /// changes Username,
     pub fn change_username(&mut self, new_username: String) -> Result<String, String> {
-        let mut req_data = HashMap::new();
-        req_data.insert("type", "changeUsername");
-        req_data.insert("newUsername", &new_username);
-        req_data.insert("sessionid", &self.session_id);
-        req_data.insert("name", &self.name);
-        req_data.insert("ownerid", &self.owner_id);
-
-        let req = Self::request(req_data, &self.api_url);
-        let head = req.headers().clone();
-        let resp = req.text().unwrap();
-
-        if !head.contains_key("signature") {
-            #[cfg(feature = "panic")]
-            {
-                panic!("response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("response was tampered with".to_string());
-            }
-        }
-        let sig = head.get("signature").unwrap().to_str().unwrap();
-        if sig != Self::make_hmac(&resp, &self.enckey_s) {
-            #[cfg(feature = "panic")]
-            {
-                panic!("Response was tampered with");
-            }
-            #[cfg(not(feature = "panic"))]
-            {
-                return Err("Response was tampered with".to_string());
-            }
-        }
-        let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
-
-        if json_rep["success"].as_bool().unwrap() {
-            Ok(json_rep["message"].as_str().unwrap().to_string())
-        } else {
-            Err(json_rep["message"].as_str().unwrap().to_string())
-        }
+        Ok(self.call(&ChangeUsername { new_username: &new_username })?.message)
     }
 
     #[cfg(feature = "web_loader")]
     pub fn web_login(&mut self, hwid: Option<String>) -> Result<(), String> {
-        use std::io::Write;
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
 
         let hwidd = match hwid {
             Some(hwid) => hwid,
-            None => self.hwid.clone(),
+            None => self.hwid.clone().unwrap_or_else(get_hwid),
         };
 
         let listener = TcpListener::bind("127.0.0.1:1337");
@@ -895,10 +547,10 @@ impl KeyauthApi {
             }
             let mut stream = stream.unwrap();
             let mut buf = [0u8; 4096];
-            stream.read(&mut buf).unwrap();
+            let n = stream.read(&mut buf).unwrap();
             let mut headers = [httparse::EMPTY_HEADER; 16];
             let mut req = httparse::Request::new(&mut headers);
-            req.parse(&buf).unwrap();
+            req.parse(&buf[..n]).unwrap();
             if req.path.unwrap().starts_with("/handshake") {
                 let s = req.path.unwrap();
                 let start = s.find("?user=").unwrap_or(0) + 6;
@@ -906,52 +558,17 @@ impl KeyauthApi {
                 let user = &s[start..end];
                 let start = s.find("&token=").unwrap_or(0) + 7;
                 let token = &s[start..];
-                let mut req_data = HashMap::new();
-                req_data.insert("type", "login");
-                req_data.insert("username", &user);
-                req_data.insert("token", &token);
-                req_data.insert("name", &self.name);
-                req_data.insert("ownerid", &self.owner_id);
-                req_data.insert("hwid", &self.hwid);
-                req_data.insert("sessionid", &self.session_id);
-
-                let req = Self::request(req_data, &self.api_url);
-                let head = req.headers().clone();
-                let resp = req.text().unwrap();
-
-                if !head.contains_key("signature") {
-                    #[cfg(feature = "panic")]
-                    {
-                        panic!("response was tampered with");
-                    }
-                    #[cfg(not(feature = "panic"))]
-                    {
-                        return Err("response was tampered with".to_string());
-                    }
-                }
-                let sig = head.get("signature").unwrap().to_str().unwrap();
-                if sig != Self::make_hmac(&resp, &self.enckey_s) {
-                    #[cfg(feature = "panic")]
-                    {
-                        panic!("Response was tampered with");
-                    }
-                    #[cfg(not(feature = "panic"))]
-                    {
-                        return Err("Response was tampered with".to_string());
-                    }
-                }
-                let json_rep: serde_json::Value = serde_json::from_str(&resp).unwrap();
-                let (status, body) = if json_rep["success"].as_bool().unwrap() {
+
+                let (reply, body) = self.exchange(&WebLogin { username: user, token, hwid: &hwidd })?;
+                let (status, body) = if reply.success {
+                    let resp: UserResponse = parse(&body)?;
                     self.username = user.to_string();
-                    self.ip = json_rep["info"]["ip"].as_str().unwrap().to_string();
-                    self.hwid = hwidd;
-                    self.create_date = json_rep["info"]["createdate"].as_str().unwrap().to_string();
-                    self.last_login = json_rep["info"]["lastlogin"].as_str().unwrap().to_string();
-                    self.subscription = json_rep["info"]["subscriptions"][0]["subscription"].as_str().unwrap().to_string();
+                    self.hwid = Some(hwidd);
+                    self.set_user_info(resp.info);
 
-                    (420, "SHEESH")
+                    (420, "SHEESH".to_string())
                 } else {
-                    (200, json_rep["message"].as_str().unwrap())
+                    (200, reply.message)
                 };
                 let response = format!(r#"HTTP/1.1 {} OK
 Access-Control-Allow-Methods: Get, Post
@@ -971,7 +588,8 @@ Server: \r\n\r\n
 
     #[cfg(feature = "web_loader")]
     pub fn button(&self, button: &str) -> Result<(), String> {
-         use std::io::Write;
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
 
         let listener = TcpListener::bind("127.0.0.1:1337");
         if listener.is_err() {
@@ -985,10 +603,10 @@ Server: \r\n\r\n
             }
             let mut stream = stream.unwrap();
             let mut buf = [0u8; 4096];
-            stream.read(&mut buf).unwrap();
+            let n = stream.read(&mut buf).unwrap();
             let mut headers = [httparse::EMPTY_HEADER; 16];
             let mut req = httparse::Request::new(&mut headers);
-            req.parse(&buf).unwrap();
+            req.parse(&buf[..n]).unwrap();
             if req.path.unwrap().starts_with(format!("/{}", button).as_str()) {
                 let response = format!(r#"HTTP/1.1 {} OK
 Access-Control-Allow-Methods: Get, Post
@@ -1006,21 +624,635 @@ Server: \r\n\r\n
         Ok(())
     }
 
-    fn request(req_data: HashMap<&str, &str>, url: &str) -> Response {
-        let client = Client::new();
-        let mut req_data_str = String::new();
-        for d in req_data {
-            req_data_str.push_str(&format!("{}={}&", d.0, d.1))
+    fn set_user_info(&mut self, info: UserInfo) {
+        self.ip = info.ip.clone();
+        self.create_date = info.createdate.clone();
+        self.last_login = info.lastlogin.clone();
+        if let Some(sub) = info.subscriptions.first() {
+            self.subscription = sub.subscription.clone();
+            self.sub_time_left = sub.timeleft;
+            self.expiry = sub.expiry.clone();
+        }
+        self.user_info = Some(info);
+    }
+
+    /// sends an endpoint request, verifies the signature and parses the typed response, Err(message) if success = false
+    fn call<E: Endpoint>(&self, endpoint: &E) -> Result<E::Response, String> {
+        let (reply, body) = self.exchange(endpoint)?;
+        if !reply.success {
+            return Err(reply.message);
+        }
+        parse(&body)
+    }
+
+    /// sends an endpoint request and verifies the signature, returns the success/message envelope and the raw body
+    fn exchange<E: Endpoint>(&self, endpoint: &E) -> Result<(Reply, String), String> {
+        let start = Instant::now();
+        let mut call = crate::trace::Call::start("1.2", E::TYPE, endpoint);
+        let result = self.verified_exchange(endpoint, &call);
+        let outcome = match &result {
+            Ok((reply, _)) => {
+                call.success(reply.success);
+                if reply.success { Outcome::Success } else { Outcome::Failed }
+            }
+            Err((kind, message)) => {
+                call.error(message);
+                Outcome::Error(*kind)
+            }
+        };
+        self.record(E::TYPE, start, outcome);
+        // counted first, tampered panics with the panic feature
+        result.map_err(|(kind, message)| match kind {
+            ErrorKind::Tampered => tampered(&message),
+            _ => message,
+        })
+    }
+
+    fn verified_exchange<E: Endpoint>(&self, endpoint: &E, call: &crate::trace::Call) -> Result<(Reply, String), (ErrorKind, String)> {
+        let resp = self.post(endpoint, call).map_err(|e| (ErrorKind::Network, e))?;
+        let head = resp.headers().clone();
+        let body = resp.text().map_err(|e| (ErrorKind::Network, e.to_string()))?;
+
+        if body == "KeyAuth_Invalid" {
+            return Err((ErrorKind::InvalidApplication, "The application doesn't exist".to_string()));
+        }
+        let sig = match head.get("signature").and_then(|s| s.to_str().ok()) {
+            Some(sig) => sig,
+            None => {
+                call.signature("missing");
+                return Err((ErrorKind::Tampered, "response was tampered with".to_string()));
+            }
+        };
+        let key = if E::SESSION { self.enckey_s.reveal() } else { self.secret.reveal() };
+        if sig != Self::make_hmac(&body, &key) {
+            call.signature("invalid");
+            return Err((ErrorKind::Tampered, "Response was tampered with".to_string()));
+        }
+        call.signature("valid");
+        let reply = parse(&body).map_err(|e| (ErrorKind::InvalidResponse, e))?;
+        Ok((reply, body))
+    }
+
+    /// sends a request whose answer isnt used (ban, log), a json answer with success false still counts as failed
+    fn fire<E: Endpoint>(&self, endpoint: &E) {
+        let start = Instant::now();
+        let mut call = crate::trace::Call::start("1.2", E::TYPE, endpoint);
+        let outcome = match self.post(endpoint, &call).and_then(|resp| resp.text().map_err(|e| e.to_string())) {
+            Ok(body) => match serde_json::from_str::<Reply>(&body) {
+                Ok(reply) => {
+                    call.success(reply.success);
+                    if reply.success { Outcome::Success } else { Outcome::Failed }
+                }
+                // keyauth doesnt answer every one of these with json
+                Err(_) => Outcome::Success,
+            },
+            Err(err) => {
+                call.error(&err);
+                Outcome::Error(ErrorKind::Network)
+            }
+        };
+        self.record(E::TYPE, start, outcome);
+    }
+
+    fn record(&self, action: &str, start: Instant, outcome: Outcome) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record(&RequestEvent { api: "1.2", action, latency: start.elapsed(), outcome });
         }
-        req_data_str = req_data_str.strip_suffix("&").unwrap().to_string();
-        client.post(url.to_string())
-            .body(req_data_str)
+    }
+
+    fn post<E: Endpoint>(&self, endpoint: &E, call: &crate::trace::Call) -> Result<reqwest::blocking::Response, String> {
+        let req = Request {
+            ty: E::TYPE,
+            sessionid: if E::SESSION { Some(&self.session_id) } else { None },
+            name: &self.name,
+            ownerid: &self.owner_id,
+            params: endpoint,
+        };
+        let send = || Client::new().post(&self.api_url)
+            .form(&req)
             .header("User-Agent", "KeyAuth")
-            .header("Content-Type", "application/x-www-form-urlencoded")
-            .send().unwrap()
+            .send();
+        let resp = match &self.rate_limiter {
+            Some(limiter) => limiter.send(send),
+            None => send(),
+        }.map_err(|e| e.to_string())?;
+        call.status(resp.status().as_u16());
+        Ok(resp)
     }
 
     fn make_hmac(message: &str, key: &str) -> String {
-        hex::encode(HMAC::mac(message, key)).to_string()
+        hex::encode(HMAC::mac(message, key))
+    }
+}
+
+/// handle to a chat channel, new messages are fetched by a background thread and delivered through [`ChatChannel::messages`] or [`ChatChannel::new_messages`].
+/// the background thread stops when the handle is dropped
+pub struct ChatChannel {
+    api: KeyauthApi,
+    channel: String,
+    delay: Duration,
+    last_sent: Option<Instant>,
+    muted: Option<String>,
+    messages: Receiver<ChatMessage>,
+    stop: Arc<AtomicBool>,
+}
+
+impl ChatChannel {
+    fn new(api: KeyauthApi, channel: &str, delay: Duration, poll_interval: Duration) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut poller = api.clone();
+        let poll_channel = channel.to_string();
+        let poll_stop = stop.clone();
+        thread::spawn(move || {
+            let mut seen = HashSet::new();
+            while !poll_stop.load(Ordering::Relaxed) {
+                if let Ok(messages) = poller.get_chat(poll_channel.clone()) {
+                    let mut batch = HashSet::new();
+                    for message in messages {
+                        let id = (message.author.clone(), message.message.clone(), message.timestamp);
+                        if !seen.contains(&id) && tx.send(message).is_err() {
+                            return;
+                        }
+                        batch.insert(id);
+                    }
+                    seen = batch;
+                }
+                thread::sleep(poll_interval);
+            }
+        });
+        Self {
+            api,
+            channel: channel.to_string(),
+            delay,
+            last_sent: None,
+            muted: None,
+            messages: rx,
+            stop,
+        }
+    }
+
+    /// name of the channel
+    pub fn channel(&self) -> &str {
+        &self.channel
+    }
+
+    /// blocking iterator over new messages, in the order keyauth returns them
+    pub fn messages(&self) -> mpsc::Iter<'_, ChatMessage> {
+        self.messages.iter()
+    }
+
+    /// messages received since the last call, doesnt block
+    pub fn new_messages(&self) -> mpsc::TryIter<'_, ChatMessage> {
+        self.messages.try_iter()
+    }
+
+    /// time left until the channel delay allows sending again
+    pub fn cooldown(&self) -> Duration {
+        match self.last_sent {
+            Some(sent) => self.delay.saturating_sub(sent.elapsed()),
+            None => Duration::ZERO,
+        }
+    }
+
+    /// Some(message from keyauth) if the last send failed because the user is muted.
+    /// keyauth's chatsend answer has no field for this, so it is a guess from "muted" being in the message
+    /// and a different wording on keyauth's side makes it return None
+    pub fn muted(&self) -> Option<&str> {
+        self.muted.as_deref()
+    }
+
+    /// sends a message, returns Err without sending if the channel delay didnt pass yet
+    pub fn send(&mut self, message: &str) -> Result<(), String> {
+        let cooldown = self.cooldown();
+        if !cooldown.is_zero() {
+            return Err(format!("channel delay, wait {} more seconds", cooldown.as_secs() + 1));
+        }
+        match self.api.send_chat_message(self.channel.clone(), message.to_string()) {
+            Ok(()) => {
+                self.last_sent = Some(Instant::now());
+                self.muted = None;
+                Ok(())
+            }
+            Err(e) => {
+                if e.to_lowercase().contains("muted") {
+                    self.muted = Some(e.clone());
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+impl Drop for ChatChannel {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn parse<T: DeserializeOwned>(body: &str) -> Result<T, String> {
+    serde_json::from_str(body).map_err(|e| e.to_string())
+}
+
+/// anonymous in memory file created with memfd_create, it is freed by the kernel when the last fd to it is closed
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+pub struct MemFile {
+    file: fs::File,
+}
+
+#[cfg(target_os = "linux")]
+impl MemFile {
+    /// creates an empty memfd, name is only shown in /proc/self/fd and doesnt have to be unique
+    pub fn new(name: &str) -> Result<Self, String> {
+        use std::ffi::CString;
+        use std::os::unix::io::FromRawFd;
+
+        let name = CString::new(name).map_err(|e| e.to_string())?;
+        let fd = unsafe { libc::memfd_create(name.as_ptr(), libc::MFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+        Ok(Self { file: unsafe { fs::File::from_raw_fd(fd) } })
+    }
+
+    /// copies bytes into a new memfd, if zero is true bytes is zeroed afterwards so the only copy is the memfd
+    pub fn from_bytes(name: &str, bytes: &mut [u8], zero: bool) -> Result<Self, String> {
+        let mem = Self::new(name)?;
+        let result = (&mem.file).write_all(bytes).map_err(|e| e.to_string());
+        if zero {
+            bytes.zeroize();
+        }
+        result.map(|_| mem)
+    }
+
+    /// raw fd of the memfd
+    pub fn fd(&self) -> std::os::unix::io::RawFd {
+        use std::os::unix::io::AsRawFd;
+        self.file.as_raw_fd()
+    }
+
+    /// /proc/self/fd path of the memfd, can be passed to Command::new or dlopen
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(format!("/proc/self/fd/{}", self.fd()))
+    }
+
+    /// the memfd as a normal file
+    pub fn file(&self) -> &fs::File {
+        &self.file
+    }
+}
+
+/// file name of a file id in the file cache
+fn cache_name(fileid: &str) -> String {
+    if !fileid.is_empty() && fileid.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        fileid.to_string()
+    } else {
+        hex::encode(fileid)
+    }
+}
+
+/// the cached file if its sha256 matches, a file that cant be read is a miss.
+/// it is hashed before anything is written so a miss can still fall back to the download
+fn cached_copy(path: &Path, sha256: &str) -> Option<fs::File> {
+    let mut file = fs::File::open(path).ok()?;
+    let mut hash = Hash::new();
+    copy_zeroized(&mut file, &mut std::io::sink(), |chunk| hash.update(chunk)).ok()?;
+    if !hex::encode(hash.finalize()).eq_ignore_ascii_case(sha256) {
+        return None;
+    }
+    file.rewind().ok()?;
+    Some(file)
+}
+
+/// io::copy with a buffer that is zeroed afterwards, inspect sees every chunk
+fn copy_zeroized<R: Read, W: Write + ?Sized>(mut reader: R, writer: &mut W, mut inspect: impl FnMut(&[u8])) -> std::io::Result<u64> {
+    let mut buf = Zeroizing::new([0u8; 16 * 1024]);
+    let mut copied = 0u64;
+    loop {
+        let n = reader.read(&mut buf[..])?;
+        if n == 0 {
+            return Ok(copied);
+        }
+        inspect(&buf[..n]);
+        writer.write_all(&buf[..n])?;
+        copied += n as u64;
+    }
+}
+
+/// where a download is written until it is verified, the path with .part appended
+fn part_path(path: &Path) -> PathBuf {
+    let mut part = path.as_os_str().to_owned();
+    part.push(".part");
+    PathBuf::from(part)
+}
+
+/// splits a file response into the hex contents, which get decoded as they arrive, and the rest of the json (skeleton)
+#[derive(Default)]
+pub(crate) struct FileScanner {
+    state: ScanState,
+    pub(crate) skeleton: Vec<u8>,
+    /// a hex digit whose partner hasnt arrived yet
+    pub(crate) nibble: Option<u8>,
+}
+
+#[derive(Default)]
+enum ScanState {
+    /// still looking for the contents key
+    #[default]
+    Key,
+    /// inside the contents string
+    Contents,
+    /// after the contents string
+    Rest,
+}
+
+impl FileScanner {
+    const KEY: &'static [u8] = b"\"contents\":\"";
+
+    pub(crate) fn feed(&mut self, chunk: &[u8], out: &mut Vec<u8>) -> Result<(), String> {
+        match self.state {
+            ScanState::Key => {
+                let start = self.skeleton.len().saturating_sub(Self::KEY.len() - 1);
+                self.skeleton.extend_from_slice(chunk);
+                if let Some(pos) = self.skeleton[start..].windows(Self::KEY.len()).position(|w| w == Self::KEY) {
+                    let rest = self.skeleton.split_off(start + pos + Self::KEY.len());
+                    self.skeleton.push(b'"');
+                    self.state = ScanState::Contents;
+                    self.feed(&rest, out)?;
+                }
+            }
+            ScanState::Contents => {
+                let end = chunk.iter().position(|&b| b == b'"');
+                for &b in &chunk[..end.unwrap_or(chunk.len())] {
+                    let v = match b {
+                        b'0'..=b'9' => b - b'0',
+                        b'a'..=b'f' => b - b'a' + 10,
+                        b'A'..=b'F' => b - b'A' + 10,
+                        _ => return Err("file contents arent valid hex".to_string()),
+                    };
+                    match self.nibble.take() {
+                        Some(high) => out.push(high << 4 | v),
+                        None => self.nibble = Some(v),
+                    }
+                }
+                if let Some(end) = end {
+                    self.state = ScanState::Rest;
+                    self.skeleton.extend_from_slice(&chunk[end + 1..]);
+                }
+            }
+            ScanState::Rest => self.skeleton.extend_from_slice(chunk),
+        }
+        Ok(())
+    }
+}
+
+fn tampered(message: &str) -> String {
+    #[cfg(feature = "panic")]
+    {
+        panic!("{}", message);
+    }
+    #[cfg(not(feature = "panic"))]
+    {
+        message.to_string()
+    }
+}
+
+/// request and response types of every 1.2 action, adding an action is a request struct and an Endpoint impl
+mod endpoint {
+    use serde::{Deserialize, Serialize};
+    use serde::de::{DeserializeOwned, IgnoredAny};
+    use super::*;
+
+    pub(super) trait Endpoint: Serialize {
+        /// value of the type parameter
+        const TYPE: &'static str;
+        /// false for requests sent before a session exists, those are signed with the app secret instead of the session enckey
+        const SESSION: bool = true;
+        type Response: DeserializeOwned;
+    }
+
+    /// parameters every request has, the endpoint's own parameters are flattened into it
+    #[derive(Serialize)]
+    pub(super) struct Request<'a, E> {
+        #[serde(rename = "type")]
+        pub ty: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub sessionid: Option<&'a str>,
+        pub name: &'a str,
+        pub ownerid: &'a str,
+        #[serde(flatten)]
+        pub params: &'a E,
+    }
+
+    /// fields every response has
+    #[derive(Deserialize)]
+    pub(super) struct Reply {
+        pub success: bool,
+        #[serde(default)]
+        pub message: String,
+        pub download: Option<String>,
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct Init<'a> {
+        pub ver: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub hash: Option<&'a str>,
+        pub enckey: &'a str,
+    }
+
+    impl Endpoint for Init<'_> {
+        const TYPE: &'static str = "init";
+        const SESSION: bool = false;
+        type Response = InitResponse;
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct Register<'a> {
+        pub username: &'a str,
+        pub pass: &'a str,
+        pub key: &'a str,
+        pub hwid: &'a str,
+    }
+
+    impl Endpoint for Register<'_> {
+        const TYPE: &'static str = "register";
+        type Response = UserResponse;
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct Upgrade<'a> {
+        pub username: &'a str,
+        pub key: &'a str,
+    }
+
+    impl Endpoint for Upgrade<'_> {
+        const TYPE: &'static str = "upgrade";
+        type Response = IgnoredAny;
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct Login<'a> {
+        pub username: &'a str,
+        pub pass: &'a str,
+        pub hwid: &'a str,
+    }
+
+    impl Endpoint for Login<'_> {
+        const TYPE: &'static str = "login";
+        type Response = UserResponse;
+    }
+
+    #[cfg(feature = "web_loader")]
+    #[derive(Serialize)]
+    pub(super) struct WebLogin<'a> {
+        pub username: &'a str,
+        pub token: &'a str,
+        pub hwid: &'a str,
+    }
+
+    #[cfg(feature = "web_loader")]
+    impl Endpoint for WebLogin<'_> {
+        const TYPE: &'static str = "login";
+        type Response = UserResponse;
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct License<'a> {
+        pub key: &'a str,
+        pub hwid: &'a str,
+    }
+
+    impl Endpoint for License<'_> {
+        const TYPE: &'static str = "license";
+        type Response = UserResponse;
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct Var<'a> {
+        pub varid: &'a str,
+    }
+
+    impl Endpoint for Var<'_> {
+        const TYPE: &'static str = "var";
+        type Response = MessageResponse;
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct File<'a> {
+        pub fileid: &'a str,
+    }
+
+    impl Endpoint for File<'_> {
+        const TYPE: &'static str = "file";
+        type Response = FileResponse;
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct Webhook<'a> {
+        pub webid: &'a str,
+        pub params: &'a str,
+    }
+
+    impl Endpoint for Webhook<'_> {
+        const TYPE: &'static str = "webhook";
+        type Response = MessageResponse;
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct CheckBlacklist {}
+
+    impl Endpoint for CheckBlacklist {
+        const TYPE: &'static str = "checkblacklist";
+        type Response = IgnoredAny;
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct Check {}
+
+    impl Endpoint for Check {
+        const TYPE: &'static str = "check";
+        type Response = IgnoredAny;
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct FetchOnline {}
+
+    impl Endpoint for FetchOnline {
+        const TYPE: &'static str = "fetchOnline";
+        type Response = OnlineResponse;
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct ChatGet<'a> {
+        pub channel: &'a str,
+    }
+
+    impl Endpoint for ChatGet<'_> {
+        const TYPE: &'static str = "chatget";
+        type Response = ChatResponse;
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct ChatSend<'a> {
+        pub channel: &'a str,
+        pub message: &'a str,
+    }
+
+    impl Endpoint for ChatSend<'_> {
+        const TYPE: &'static str = "chatsend";
+        type Response = IgnoredAny;
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct Ban {}
+
+    impl Endpoint for Ban {
+        const TYPE: &'static str = "ban";
+        type Response = IgnoredAny;
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct SetVar<'a> {
+        pub var: &'a str,
+        pub data: &'a str,
+    }
+
+    impl Endpoint for SetVar<'_> {
+        const TYPE: &'static str = "setvar";
+        type Response = IgnoredAny;
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct GetVar<'a> {
+        pub var: &'a str,
+    }
+
+    impl Endpoint for GetVar<'_> {
+        const TYPE: &'static str = "getvar";
+        type Response = GetVarResponse;
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct Log<'a> {
+        pub message: &'a str,
+        pub pcuser: &'a str,
+    }
+
+    impl Endpoint for Log<'_> {
+        const TYPE: &'static str = "log";
+        type Response = IgnoredAny;
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct ChangeUsername<'a> {
+        #[serde(rename = "newUsername")]
+        pub new_username: &'a str,
+    }
+
+    impl Endpoint for ChangeUsername<'_> {
+        const TYPE: &'static str = "changeUsername";
+        type Response = MessageResponse;
     }
 }